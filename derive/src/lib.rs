@@ -3,8 +3,8 @@ use proc_macro2::{Span, TokenStream, TokenTree};
 use proc_macro_error::{abort, proc_macro_error};
 use quote::{format_ident, quote, ToTokens};
 use syn::{
-    punctuated::Punctuated, spanned::Spanned, Attribute, Data, DeriveInput, Expr, Lit, Meta, Path,
-    Token,
+    punctuated::Punctuated, spanned::Spanned, Attribute, Data, DataEnum, DeriveInput, Expr, Fields,
+    Ident, Lit, Meta, Path, Token, Type,
 };
 
 /// Derive macro for `BevyObject`. This largely mirrors `Bundle` but supports additional types of fields.
@@ -14,6 +14,10 @@ use syn::{
 /// * `DefaultInit<T>` initializes a non-serialize component with `FromWorld`.
 /// * `Child<T>` inserts/finds a single child `BevyObject` during de/serialization.
 /// * `ChildVec<T>` inserts/finds multiple children `BevyObject` during de/serialization.
+/// * `ChildMap<K, T>` inserts/finds multiple children `BevyObject` keyed by component `K`.
+/// * `ChildTree<T>` inserts/finds descendants `BevyObject` of arbitrary depth.
+/// * `ParentObject<T, K>` inserts/finds a `BevyObject` on the parent, reusing a parent with an equal key component `K` on load.
+/// * `Adapted<T, A>` inserts/finds a component without serde support via adapter `A`.
 ///
 /// `Component` is automatically `BevyObject` so no need to implement on them.
 ///
@@ -41,6 +45,11 @@ use syn::{
 /// Ignore the `QueryFilter` generated by this field,
 /// this is useful for causing errors in case of bad data during serialization.
 ///
+/// * `#[bevy_object(adapter = "Adapter")]`
+///
+/// Serialize a component without serde support via `Adapter: SerializeAs<Component>`,
+/// equivalent to `Adapted<Component, Adapter>`. Not supported in `query` mode.
+///
 /// # Serde Attributes
 ///
 /// You can specify serde attributes `#[serde]` in this macro but you don't need to actually derive serde.
@@ -50,6 +59,23 @@ use syn::{
 /// this means we don't have much support for attributes like `skip_serializing_if`.
 ///
 /// `#[serde(skip)]` and `#[serde(default)]` is supported on `Maybe` and `DefaultInit`.
+///
+/// # Enums
+///
+/// An enum of newtype variants containing `BevyObject`s is a `BevyObject`
+/// that matches any of its variants, serialized as the first variant whose filter matches.
+/// Use `ChildVec<Enum>` to serialize children of different types in one sequence
+/// while preserving the order of `Children`.
+///
+/// ```ignore
+/// #[derive(BevyObject)]
+/// enum Gear {
+///     Sword(Sword),
+///     Shield(SerializeShield),
+/// }
+/// ```
+///
+/// Only externally tagged and adjacently tagged representations are supported.
 #[proc_macro_error]
 #[proc_macro_derive(BevyObject, attributes(bevy_object, serde))]
 pub fn serialization_archetype(tokens: TokenStream1) -> TokenStream1 {
@@ -69,12 +95,8 @@ fn token_stream_is_ident(stream: &TokenStream, name: &str) -> bool {
 
 fn parse_attr(attr: &Attribute, name: &str) -> bool {
     match &attr.meta {
-        Meta::List(list) => {
-            if list.path.get_ident().is_some_and(|i| i == "bevy_object") {
-                token_stream_is_ident(&list.tokens, name)
-            } else {
-                false
-            }
+        Meta::List(list) if list.path.get_ident().is_some_and(|i| i == "bevy_object") => {
+            token_stream_is_ident(&list.tokens, name)
         }
         _ => false,
     }
@@ -87,7 +109,7 @@ fn parse_attr_main(
     parent: &mut Option<Path>,
 ) {
     let Meta::List(list) = &attr.meta else { return };
-    if !list.path.get_ident().is_some_and(|i| i == "bevy_object") {
+    if list.path.get_ident().is_none_or(|i| i != "bevy_object") {
         return;
     };
     let Ok(nested) = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
@@ -113,6 +135,28 @@ fn parse_attr_main(
     }
 }
 
+fn parse_adapter(attr: &Attribute) -> Option<Type> {
+    let Meta::List(list) = &attr.meta else {
+        return None;
+    };
+    if list.path.get_ident().is_none_or(|i| i != "bevy_object") {
+        return None;
+    };
+    let nested = attr
+        .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        .ok()?;
+    nested.into_iter().find_map(|meta| match meta {
+        Meta::NameValue(meta) if meta.path.is_ident("adapter") => {
+            let Expr::Lit(lit) = meta.value else {
+                return None;
+            };
+            let Lit::Str(lit) = lit.lit else { return None };
+            syn::parse_str(&lit.value()).ok()
+        }
+        _ => None,
+    })
+}
+
 fn is_forwarded(attr: &Attribute) -> bool {
     match &attr.meta {
         Meta::List(list) => list.path.get_ident().is_some_and(|i| i == "serde"),
@@ -120,6 +164,22 @@ fn is_forwarded(attr: &Attribute) -> bool {
     }
 }
 
+/// Returns true if a forwarded `#[serde]` attribute changes how a field is deserialized.
+fn is_custom_deserialize(attr: &Attribute) -> bool {
+    let Meta::List(list) = &attr.meta else {
+        return false;
+    };
+    list.tokens.clone().into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => {
+            ident == "skip"
+                || ident == "skip_deserializing"
+                || ident == "with"
+                || ident == "deserialize_with"
+        }
+        _ => false,
+    })
+}
+
 fn roll_tuple<T: ToTokens>(types: &[T]) -> TokenStream {
     let mut result = quote! {()};
     for item in types {
@@ -133,8 +193,10 @@ fn serialization_archetype2(tokens: TokenStream) -> TokenStream {
         abort!(Span::call_site(), "Invalid input.")
     };
 
-    let Data::Struct(st) = result.data else {
-        abort!(result.span(), "Invalid struct.")
+    let st = match result.data {
+        Data::Struct(st) => st,
+        Data::Enum(en) => return enum_archetype(result.ident, result.attrs, en),
+        Data::Union(_) => abort!(result.span(), "Invalid struct."),
     };
 
     let name = result.ident;
@@ -157,13 +219,26 @@ fn serialization_archetype2(tokens: TokenStream) -> TokenStream {
     main_attrs = result.attrs.into_iter().filter(is_forwarded).collect();
 
     let crate0 = quote! {::bevy_serde_lens};
+    let mut field_fns = TokenStream::new();
+    let mut field_types = Vec::new();
+    let mut ext = TokenStream::new();
 
     for field in st.fields {
         let Some(name) = field.ident else {
             abort!(field.span(), "Tuple struct is not supported.")
         };
-        let ty = field.ty;
+        let ty = match field.attrs.iter().find_map(parse_adapter) {
+            Some(_) if is_query => abort!(name.span(), "Adapter is not supported in query mode."),
+            Some(adapter) => {
+                let ty = field.ty;
+                syn::parse_quote!(#crate0::Adapted<#ty, #adapter>)
+            }
+            None => field.ty,
+        };
+        let name_str = name.to_string();
+        let de_fn = format_ident!("__bevy_serde_lens_de_{name}");
         fields.push(name);
+        field_types.push(ty.clone());
         types.push(quote! {
             <#ty as #crate0::BindProject>::To
         });
@@ -178,17 +253,34 @@ fn serialization_archetype2(tokens: TokenStream) -> TokenStream {
             });
         }
         queries.push(quote! {<#ty as #crate0::BindProjectQuery>::Data});
-        field_attrs.push(field.attrs.into_iter().filter(is_forwarded).collect())
+        let mut attrs: Vec<_> = field.attrs.into_iter().filter(is_forwarded).collect();
+        if !attrs.iter().any(is_custom_deserialize) {
+            let de_fn_str = de_fn.to_string();
+            field_fns.extend(quote! {
+                fn #de_fn<'de, D: #crate0::serde::Deserializer<'de>, T: #crate0::serde::Deserialize<'de>>(
+                    deserializer: D
+                ) -> Result<T, D::Error> {
+                    #crate0::path::deserialize_field(#name_str, deserializer)
+                }
+            });
+            attrs.push(syn::parse_quote!(#[serde(deserialize_with = #de_fn_str)]));
+        }
+        field_attrs.push(attrs)
     }
 
     let filter = roll_tuple(&filters);
 
+    ext.extend(quote! {
+        fn remove(entity: &mut #crate0::EntityWorldMut) {
+            #(<#field_types as #crate0::BindProject>::remove(entity);)*
+        }
+    });
+
     let data = if is_query {
         roll_tuple(&queries)
     } else {
         quote!(())
     };
-    let mut ext = TokenStream::new();
 
     if is_query {
         let rolled_fields = roll_tuple(&fields);
@@ -217,6 +309,14 @@ fn serialization_archetype2(tokens: TokenStream) -> TokenStream {
 
     quote!(
         const _: () = {
+            #field_fns
+
+            // Fields of the struct only describe the binding and are never read otherwise.
+            #[allow(dead_code)]
+            fn __bevy_serde_lens_fields(this: &#name) {
+                #(let _ = &this.#fields;)*
+            }
+
             #[derive(#crate0::serde::Serialize, #crate0::serde::Deserialize)]
             #(#main_attrs)*
             pub struct #name_binding {
@@ -247,3 +347,127 @@ fn serialization_archetype2(tokens: TokenStream) -> TokenStream {
         };
     )
 }
+
+fn enum_archetype(name: Ident, attrs: Vec<Attribute>, en: DataEnum) -> TokenStream {
+    let mut name_str = name.to_string();
+    let mut parent = None;
+    let mut is_query = false;
+
+    for attr in &attrs {
+        parse_attr_main(attr, &mut is_query, &mut name_str, &mut parent);
+    }
+
+    if is_query {
+        abort!(name.span(), "Query is not supported on enums.")
+    }
+
+    let name_binding = format_ident!("{name}Binding");
+    let main_attrs: Vec<_> = attrs.into_iter().filter(is_forwarded).collect();
+    let crate0 = quote! {::bevy_serde_lens};
+
+    let mut variants = Vec::new();
+    let mut types = Vec::new();
+    let mut variant_attrs = Vec::<Vec<_>>::new();
+    let mut variant_fns = TokenStream::new();
+
+    for variant in en.variants {
+        let span = variant.span();
+        let Fields::Unnamed(fields) = variant.fields else {
+            abort!(span, "Only newtype variants are supported.")
+        };
+        let mut fields = fields.unnamed.into_iter();
+        let (Some(field), None) = (fields.next(), fields.next()) else {
+            abort!(span, "Only newtype variants are supported.")
+        };
+        let name = variant.ident;
+        let name_str = name.to_string();
+        let de_fn = format_ident!("__bevy_serde_lens_de_{name}");
+        let mut attrs: Vec<_> = variant.attrs.into_iter().filter(is_forwarded).collect();
+        if !attrs.iter().any(is_custom_deserialize) {
+            let de_fn_str = de_fn.to_string();
+            variant_fns.extend(quote! {
+                fn #de_fn<'de, D: #crate0::serde::Deserializer<'de>, T: #crate0::serde::Deserialize<'de>>(
+                    deserializer: D
+                ) -> Result<T, D::Error> {
+                    #crate0::path::deserialize_field(#name_str, deserializer)
+                }
+            });
+            attrs.push(syn::parse_quote!(#[serde(deserialize_with = #de_fn_str)]));
+        }
+        variants.push(name);
+        types.push(field.ty);
+        variant_attrs.push(attrs);
+    }
+
+    let indices = 0..variants.len();
+
+    let parent = parent.map(|parent| {
+        quote! {
+            fn get_root(world: &mut #crate0::World) -> Option<#crate0::EntityWorldMut> {
+                #parent()
+            }
+        }
+    });
+
+    let definition = quote! {
+        #[allow(dead_code)]
+        #(#main_attrs)*
+        enum #name {
+            #(#(#variant_attrs)* #variants(<#types as #crate0::BevyObject>::Object),)*
+        }
+    };
+
+    quote!(
+        const _: () = {
+            #variant_fns
+
+            pub struct #name_binding;
+
+            impl #crate0::ZstInit for #name_binding {
+                fn init() -> Self {
+                    Self
+                }
+            }
+
+            impl #crate0::serde::Serialize for #name_binding {
+                fn serialize<S: #crate0::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    #[derive(#crate0::serde::Serialize)]
+                    #definition
+                    let value = match #crate0::match_variant::<S>(&[
+                        #(<#types as #crate0::BevyObject>::filter,)*
+                    ])? {
+                        #(#indices => #name::#variants(#crate0::ZstInit::init()),)*
+                        _ => unreachable!(),
+                    };
+                    #crate0::serde::Serialize::serialize(&value, serializer)
+                }
+            }
+
+            impl<'de> #crate0::serde::Deserialize<'de> for #name_binding {
+                fn deserialize<D: #crate0::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    #[derive(#crate0::serde::Deserialize)]
+                    #definition
+                    <#name as #crate0::serde::Deserialize>::deserialize(deserializer)?;
+                    Ok(Self)
+                }
+            }
+
+            impl #crate0::BevyObject for #name {
+                const IS_QUERY: bool = false;
+                type Data = ();
+                type Filter = #crate0::Or<(#(<#types as #crate0::BevyObject>::Filter,)*)>;
+                type Object = #name_binding;
+
+                fn name() -> &'static str {
+                    #name_str
+                }
+
+                fn remove(entity: &mut #crate0::EntityWorldMut) {
+                    #(<#types as #crate0::BevyObject>::remove(entity);)*
+                }
+
+                #parent
+            }
+        };
+    )
+}
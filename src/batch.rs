@@ -1,33 +1,116 @@
 use crate::{
-    entity_scope, ser_scope, BevyObject, Root, SerializeNonSend, SerializeResource, ZstInit,
+    entity_scope, journal, ser_scope, BevyObject, Root, SerializeNonSend, SerializeResource,
+    ZstInit,
 };
 use bevy_ecs::{entity::Entity, system::Resource, world::World};
 use bevy_reflect::TypePath;
+use bevy_serde_lens_core::with_world_mut;
 use serde::{
-    de::{DeserializeOwned, MapAccess, Visitor},
-    ser::SerializeMap,
+    de::{DeserializeOwned, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeTuple},
     Deserialize, Deserializer, Serialize, Serializer,
 };
 use std::{borrow::Cow, cell::RefCell, marker::PhantomData};
 
+/// Resource that controls how a batch handles missing and unknown sections during `load`.
+///
+/// By default, missing sections are allowed and unknown sections are errors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource)]
+pub struct SectionPolicy {
+    /// If true, error if a section is missing.
+    pub deny_missing: bool,
+    /// If true, skip unknown sections and report them in
+    /// [`LoadReport::unknown_sections`](crate::LoadReport::unknown_sections) instead of erroring.
+    pub skip_unknown: bool,
+}
+
+impl SectionPolicy {
+    /// Error on missing and unknown sections.
+    pub const fn strict() -> Self {
+        SectionPolicy {
+            deny_missing: true,
+            skip_unknown: false,
+        }
+    }
+
+    /// Allow missing sections and skip unknown sections,
+    /// which allows loading saves created by newer versions.
+    pub const fn forward_compatible() -> Self {
+        SectionPolicy {
+            deny_missing: false,
+            skip_unknown: true,
+        }
+    }
+
+    /// Obtain the policy of the active world.
+    pub(crate) fn get<E: serde::de::Error>() -> Result<Self, E> {
+        with_world_mut(|world| {
+            world
+                .get_resource::<SectionPolicy>()
+                .copied()
+                .unwrap_or_default()
+        })
+        .map_err(serde::de::Error::custom)
+    }
+
+    /// Returns `Ok` and records the section if unknown sections should be skipped.
+    pub(crate) fn unknown<E: serde::de::Error>(name: &str) -> Result<(), E> {
+        if Self::get::<E>()?.skip_unknown {
+            journal::record_unknown_section(name);
+            Ok(())
+        } else {
+            Err(serde::de::Error::custom(format!(
+                "Unknown type name {name}."
+            )))
+        }
+    }
+}
+
 /// A batch serialization type.
 pub trait BatchSerialization {
     type De: DeserializeOwned + ZstInit;
+    /// Number of sections, `0` if only known with world access.
     const LEN: usize;
+    /// Number of sections on `world`.
+    fn len(_world: &mut World) -> usize {
+        Self::LEN
+    }
+    /// Names of sections in this batch.
+    fn names() -> Vec<&'static str>;
+    /// Returns true if section `name` is a sequence of entities.
+    fn is_entities(name: &str) -> bool;
     fn despawn(world: &mut World);
+    /// Returns root entities of all entity sections.
+    fn bound_entities(world: &mut World) -> Vec<Entity>;
+    /// Despawn sections that are not entities and not in `sections`.
+    fn despawn_missing(world: &mut World, sections: &[&'static str]);
     fn serialize<S: Serializer>(world: &mut World, s: S) -> Result<S::Ok, S::Error>;
     fn save_map<S: SerializeMap>(serializer: &mut S, world: &mut World) -> Result<(), S::Error>;
     fn deserialize_map<'de, M>(name: &str, map: &mut M) -> Result<(), M::Error>
     where
         M: MapAccess<'de>;
+    /// Serialize each section as an element of a tuple, used by [`Positional`].
+    fn save_tuple<S: SerializeTuple>(serializer: &mut S, world: &mut World)
+        -> Result<(), S::Error>;
+    /// Deserialize each section from an element of a tuple, used by [`Positional`].
+    fn deserialize_tuple<'de, A>(seq: &mut A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>;
 }
 
 /// A Single item in [`BatchSerialization`].
 pub trait SerializeWorld {
     type De: DeserializeOwned + ZstInit;
+    /// If true, serializes as a sequence of independent entities,
+    /// which can be loaded in chunks.
+    const IS_ENTITIES: bool = false;
     fn name() -> &'static str;
     fn serialize<S: Serializer>(world: &mut World, s: S) -> Result<S::Ok, S::Error>;
     fn despawn(world: &mut World);
+    /// Returns root entities bound to this type, empty if not [`Self::IS_ENTITIES`].
+    fn bound_entities(_world: &mut World) -> Vec<Entity> {
+        Vec::new()
+    }
 }
 
 impl<T> BatchSerialization for T
@@ -38,10 +121,28 @@ where
 
     const LEN: usize = 1;
 
+    fn names() -> Vec<&'static str> {
+        vec![Self::name()]
+    }
+
+    fn is_entities(name: &str) -> bool {
+        T::IS_ENTITIES && name == Self::name()
+    }
+
     fn despawn(world: &mut World) {
         <T as SerializeWorld>::despawn(world)
     }
 
+    fn bound_entities(world: &mut World) -> Vec<Entity> {
+        <T as SerializeWorld>::bound_entities(world)
+    }
+
+    fn despawn_missing(world: &mut World, sections: &[&'static str]) {
+        if !T::IS_ENTITIES && !sections.contains(&Self::name()) {
+            <T as SerializeWorld>::despawn(world)
+        }
+    }
+
     fn serialize<S: Serializer>(world: &mut World, s: S) -> Result<S::Ok, S::Error> {
         <T as SerializeWorld>::serialize(world, s)
     }
@@ -61,6 +162,7 @@ where
         M: MapAccess<'de>,
     {
         if name == Self::name() {
+            journal::record_section(Self::name());
             map.next_value::<T::De>()?;
             Ok(())
         } else {
@@ -69,6 +171,30 @@ where
             )))
         }
     }
+
+    fn save_tuple<S: SerializeTuple>(
+        serializer: &mut S,
+        world: &mut World,
+    ) -> Result<(), S::Error> {
+        serializer.serialize_element(&SerializeWorldLens {
+            world: RefCell::new(world),
+            p: PhantomData::<T>,
+        })
+    }
+
+    fn deserialize_tuple<'de, A>(seq: &mut A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        journal::record_section(Self::name());
+        match seq.next_element::<T::De>()? {
+            Some(_) => Ok(()),
+            None => Err(serde::de::Error::custom(format!(
+                "Missing section {}.",
+                Self::name()
+            ))),
+        }
+    }
 }
 
 pub(crate) struct SerializeWorldLens<'t, S: SerializeWorld> {
@@ -90,6 +216,7 @@ where
     T: BevyObject,
 {
     type De = Root<T>;
+    const IS_ENTITIES: bool = true;
 
     fn name() -> &'static str {
         <T as BevyObject>::name()
@@ -115,12 +242,15 @@ where
     }
 
     fn despawn(world: &mut World) {
-        let mut query = world.query_filtered::<Entity, T::Filter>();
-        let queue = query.iter(world).collect::<Vec<_>>();
-        for entity in queue {
+        for entity in <Self as SerializeWorld>::bound_entities(world) {
             bevy_hierarchy::despawn_with_children_recursive(world, entity);
         }
     }
+
+    fn bound_entities(world: &mut World) -> Vec<Entity> {
+        let mut query = world.query_filtered::<Entity, T::Filter>();
+        query.iter(world).collect()
+    }
 }
 
 impl<T> SerializeWorld for SerializeResource<T>
@@ -175,12 +305,43 @@ where
     B: BatchSerialization,
 {
     type De = Self;
-    const LEN: usize = B::LEN + 1;
+    const LEN: usize = {
+        assert!(B::LEN != 0, "`DynamicBatch` cannot be used in `batch!`.");
+        B::LEN + 1
+    };
+    fn names() -> Vec<&'static str> {
+        let mut names = Vec::with_capacity(Self::LEN);
+        names.push(A::name());
+        names.extend(B::names());
+        names
+    }
+
+    fn is_entities(name: &str) -> bool {
+        if name == A::name() {
+            A::IS_ENTITIES
+        } else {
+            B::is_entities(name)
+        }
+    }
+
     fn despawn(world: &mut World) {
         A::despawn(world);
         B::despawn(world);
     }
 
+    fn bound_entities(world: &mut World) -> Vec<Entity> {
+        let mut entities = A::bound_entities(world);
+        entities.extend(B::bound_entities(world));
+        entities
+    }
+
+    fn despawn_missing(world: &mut World, sections: &[&'static str]) {
+        if !A::IS_ENTITIES && !sections.contains(&A::name()) {
+            A::despawn(world);
+        }
+        B::despawn_missing(world, sections);
+    }
+
     fn save_map<S: SerializeMap>(serializer: &mut S, world: &mut World) -> Result<(), S::Error> {
         serializer.serialize_entry(
             A::name(),
@@ -195,7 +356,6 @@ where
     fn serialize<S: Serializer>(world: &mut World, s: S) -> Result<S::Ok, S::Error> {
         let mut map = s.serialize_map(Some(Self::LEN))?;
         Self::save_map(&mut map, world)?;
-        B::save_map(&mut map, world)?;
         map.end()
     }
 
@@ -204,12 +364,29 @@ where
         M: MapAccess<'de>,
     {
         if name == A::name() {
+            journal::record_section(A::name());
             map.next_value::<A::De>()?;
         } else {
             B::deserialize_map(name, map)?;
         }
         Ok(())
     }
+
+    fn save_tuple<S: SerializeTuple>(
+        serializer: &mut S,
+        world: &mut World,
+    ) -> Result<(), S::Error> {
+        A::save_tuple(serializer, world)?;
+        B::save_tuple(serializer, world)
+    }
+
+    fn deserialize_tuple<'de, M>(seq: &mut M) -> Result<(), M::Error>
+    where
+        M: SeqAccess<'de>,
+    {
+        A::deserialize_tuple(seq)?;
+        B::deserialize_tuple(seq)
+    }
 }
 
 impl<'de, A, B> Deserialize<'de> for Join<A, B>
@@ -240,14 +417,147 @@ where
     where
         M: MapAccess<'de>,
     {
+        let names = Self::names();
+        let mut found = Vec::new();
         while let Some(key) = map.next_key::<Cow<str>>()? {
+            let Some(name) = names.iter().find(|x| **x == key.as_ref()) else {
+                SectionPolicy::unknown::<M::Error>(&key)?;
+                map.next_value::<IgnoredAny>()?;
+                continue;
+            };
+            found.push(*name);
             if key.as_ref() == A::name() {
+                journal::record_section(A::name());
                 map.next_value::<A::De>()?;
             } else {
                 B::deserialize_map(key.as_ref(), &mut map)?;
             }
-            //Self::deserialize_map(&key, &mut map)?
+        }
+        if SectionPolicy::get::<M::Error>()?.deny_missing {
+            if let Some(name) = names.iter().find(|x| !found.contains(x)) {
+                return Err(serde::de::Error::custom(format!("Missing section {name}.")));
+            }
         }
         Ok(Join(PhantomData))
     }
 }
+
+/// A batch serialized as a tuple of sections instead of a map of names.
+///
+/// This is smaller and faster to load in non-self-describing formats like `postcard`,
+/// but sections are identified by position, so adding, removing or reordering sections
+/// breaks existing saves. [`SectionPolicy`] does not apply, all sections must be present.
+///
+/// A batch of a single type is serialized the same as without [`Positional`].
+///
+/// # Example
+/// ```
+/// type Snapshot = Positional<batch!(Character, Monster, SerializeResource<Terrain>)>;
+/// world.save::<Snapshot, _>(serializer)
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Positional<B>(PhantomData<B>);
+
+impl<B: BatchSerialization> BatchSerialization for Positional<B> {
+    type De = DeserializePositional<B>;
+    const LEN: usize = B::LEN;
+
+    fn len(world: &mut World) -> usize {
+        B::len(world)
+    }
+
+    fn names() -> Vec<&'static str> {
+        B::names()
+    }
+
+    fn is_entities(name: &str) -> bool {
+        B::is_entities(name)
+    }
+
+    fn despawn(world: &mut World) {
+        B::despawn(world)
+    }
+
+    fn bound_entities(world: &mut World) -> Vec<Entity> {
+        B::bound_entities(world)
+    }
+
+    fn despawn_missing(world: &mut World, sections: &[&'static str]) {
+        B::despawn_missing(world, sections)
+    }
+
+    fn serialize<S: Serializer>(world: &mut World, s: S) -> Result<S::Ok, S::Error> {
+        if B::LEN == 1 {
+            return B::serialize(world, s);
+        }
+        let mut tuple = s.serialize_tuple(B::len(world))?;
+        B::save_tuple(&mut tuple, world)?;
+        tuple.end()
+    }
+
+    fn save_map<S: SerializeMap>(serializer: &mut S, world: &mut World) -> Result<(), S::Error> {
+        B::save_map(serializer, world)
+    }
+
+    fn deserialize_map<'de, M>(name: &str, map: &mut M) -> Result<(), M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        B::deserialize_map(name, map)
+    }
+
+    fn save_tuple<S: SerializeTuple>(
+        serializer: &mut S,
+        world: &mut World,
+    ) -> Result<(), S::Error> {
+        B::save_tuple(serializer, world)
+    }
+
+    fn deserialize_tuple<'de, A>(seq: &mut A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        B::deserialize_tuple(seq)
+    }
+}
+
+/// Deserialize a [`Positional`] batch.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct DeserializePositional<B>(PhantomData<B>);
+
+impl<B> ZstInit for DeserializePositional<B> {
+    fn init() -> Self {
+        DeserializePositional(PhantomData)
+    }
+}
+
+impl<'de, B: BatchSerialization> Deserialize<'de> for DeserializePositional<B> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if B::LEN == 1 {
+            B::De::deserialize(deserializer)?;
+            return Ok(DeserializePositional(PhantomData));
+        }
+        let len = with_world_mut(|world| B::len(world)).map_err(serde::de::Error::custom)?;
+        deserializer.deserialize_tuple(len, DeserializePositional(PhantomData))
+    }
+}
+
+impl<'de, B: BatchSerialization> Visitor<'de> for DeserializePositional<B> {
+    type Value = DeserializePositional<B>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("tuple of types")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        B::deserialize_tuple(&mut seq)?;
+        Ok(DeserializePositional(PhantomData))
+    }
+}
@@ -0,0 +1,357 @@
+//! Module for saving and applying changes since a [`Tick`].
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    cell::Cell,
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use bevy_ecs::{
+    component::{Component, ComponentId, ComponentTicks, Tick},
+    entity::Entity,
+    event::ManualEventReader,
+    observer::Trigger,
+    query::With,
+    removal_detection::RemovedComponentEntity,
+    system::Resource,
+    world::{DeferredWorld, EntityRef, OnRemove, World},
+};
+use bevy_hierarchy::{Children, Parent};
+use bevy_serde_lens_core::with_world_mut;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{
+    de::{DeserializeOwned, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{entity_scope, merge::MergeRoot, safe_despawn, ser_scope, value::Value, BevyObject};
+
+thread_local! {
+    static PROBE: Cell<Option<(Tick, Tick)>> = const { Cell::new(None) };
+    static CHANGED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Mark the current delta scope as changed if `ticks` is newer than `since`.
+pub(crate) fn probe(ticks: ComponentTicks) {
+    if let Some((since, this_run)) = PROBE.get() {
+        if ticks.is_changed(since, this_run) {
+            CHANGED.set(true)
+        }
+    }
+}
+
+/// Run a function and returns if any component probed is changed since `since`.
+fn changed_scope<T>(since: Tick, this_run: Tick, f: impl FnOnce() -> T) -> (T, bool) {
+    let previous = PROBE.replace(Some((since, this_run)));
+    let changed = CHANGED.replace(false);
+    let result = f();
+    PROBE.set(previous);
+    (result, CHANGED.replace(changed))
+}
+
+/// Removals recorded for delta saves.
+#[derive(Resource, Default)]
+pub(crate) struct DeltaTracker {
+    /// Keys `K` of despawned [`BevyObject`]s and the tick they were removed at.
+    tombstones: FxHashMap<(&'static str, TypeId), Box<dyn Any + Send + Sync>>,
+    /// Readers of [`RemovedComponents`](bevy_ecs::removal_detection::RemovedComponents).
+    readers: FxHashMap<ComponentId, ManualEventReader<RemovedComponentEntity>>,
+    /// Entities with removed components and the tick the removal was recorded at.
+    removed: FxHashMap<Entity, Tick>,
+}
+
+impl DeltaTracker {
+    fn tombstones<K: Component + Eq + Hash>(
+        &self,
+        name: &'static str,
+    ) -> Option<&FxHashMap<K, Tick>> {
+        self.tombstones
+            .get(&(name, TypeId::of::<K>()))
+            .and_then(|x| x.downcast_ref())
+    }
+
+    fn tombstones_mut<K: Component + Eq + Hash>(
+        &mut self,
+        name: &'static str,
+    ) -> Option<&mut FxHashMap<K, Tick>> {
+        self.tombstones
+            .get_mut(&(name, TypeId::of::<K>()))
+            .and_then(|x| x.downcast_mut())
+    }
+}
+
+/// Returns an observer that records a tombstone when `K` is removed from an object
+/// matching `filter`, including on despawn.
+fn record_tombstone<K: Component + Eq + Hash + Clone>(
+    name: &'static str,
+    filter: fn(&EntityRef) -> bool,
+) -> impl FnMut(Trigger<OnRemove, K>, DeferredWorld) {
+    move |trigger, mut world| {
+        let Some(entity) = world.get_entity(trigger.entity()) else {
+            return;
+        };
+        if !filter(&entity) {
+            return;
+        }
+        let Some(key) = entity.get::<K>().cloned() else {
+            return;
+        };
+        let tick = world.read_change_tick();
+        if let Some(tombstones) = world
+            .get_resource_mut::<DeltaTracker>()
+            .as_mut()
+            .and_then(|x| x.tombstones_mut::<K>(name))
+        {
+            tombstones.insert(key, tick);
+        }
+    }
+}
+
+/// Start recording tombstones of `T` by key `K`, does nothing if already tracked.
+pub(crate) fn track_delta<T: BevyObject, K: Component + Eq + Hash + Clone>(world: &mut World) {
+    let mut tracker = world.get_resource_or_insert_with(DeltaTracker::default);
+    if tracker.tombstones::<K>(T::name()).is_some() {
+        return;
+    }
+    tracker.tombstones.insert(
+        (T::name(), TypeId::of::<K>()),
+        Box::new(FxHashMap::<K, Tick>::default()),
+    );
+    world.observe(record_tombstone::<K>(T::name(), T::filter));
+}
+
+/// Read [`RemovedComponents`](bevy_ecs::removal_detection::RemovedComponents) of all components
+/// and record the current tick for entities with removed components.
+pub fn record_removals(world: &mut World) {
+    let tick = world.read_change_tick();
+    world.resource_scope::<DeltaTracker, _>(|world, mut tracker| {
+        let tracker = tracker.as_mut();
+        for (id, events) in world.removed_components().iter() {
+            let reader = tracker.readers.entry(*id).or_default();
+            for entity in reader.read(events) {
+                tracker.removed.insert(entity.clone().into(), tick);
+            }
+        }
+        tracker
+            .removed
+            .retain(|entity, _| world.get_entity(*entity).is_some());
+    })
+}
+
+/// Returns true if the hierarchy of `entity` has changed or had components removed since `since`.
+fn structure_changed(
+    world: &World,
+    tracker: &DeltaTracker,
+    entity: Entity,
+    since: Tick,
+    this_run: Tick,
+) -> bool {
+    let mut stack = vec![entity];
+    while let Some(entity) = stack.pop() {
+        if tracker
+            .removed
+            .get(&entity)
+            .is_some_and(|tick| tick.is_newer_than(since, this_run))
+        {
+            return true;
+        }
+        let Some(entity) = world.get_entity(entity) else {
+            continue;
+        };
+        if entity
+            .get_change_ticks::<Parent>()
+            .is_some_and(|ticks| ticks.is_changed(since, this_run))
+        {
+            return true;
+        }
+        if entity
+            .get_change_ticks::<Children>()
+            .is_some_and(|ticks| ticks.is_changed(since, this_run))
+        {
+            return true;
+        }
+        if let Some(children) = entity.get::<Children>() {
+            stack.extend(children.iter().copied());
+        }
+    }
+    false
+}
+
+/// Returns true if a component of `entity` is added or changed since `since`.
+fn components_changed(entity: EntityRef, since: Tick, this_run: Tick) -> bool {
+    entity.archetype().components().any(|id| {
+        entity
+            .get_change_ticks_by_id(id)
+            .is_some_and(|ticks| ticks.is_changed(since, this_run))
+    })
+}
+
+/// Returns true if a component of `entity`, its ancestors or its descendants
+/// is added or changed since `since`.
+///
+/// This includes every component an object can serialize, so objects where this is false
+/// are skipped without serializing them.
+fn tree_changed(world: &World, entity: Entity, since: Tick, this_run: Tick) -> bool {
+    let mut parent = world.get::<Parent>(entity).map(|x| x.get());
+    while let Some(entity) = parent.and_then(|x| world.get_entity(x)) {
+        if components_changed(entity, since, this_run) {
+            return true;
+        }
+        parent = entity.get::<Parent>().map(|x| x.get());
+    }
+    let mut stack = vec![entity];
+    while let Some(entity) = stack.pop() {
+        let Some(entity) = world.get_entity(entity) else {
+            continue;
+        };
+        if components_changed(entity, since, this_run) {
+            return true;
+        }
+        if let Some(children) = entity.get::<Children>() {
+            stack.extend(children.iter().copied());
+        }
+    }
+    false
+}
+
+const FIELDS: &[&str] = &["changed", "removed"];
+
+struct Delta<K> {
+    changed: Vec<Value>,
+    removed: Vec<K>,
+}
+
+impl<K: Serialize> Serialize for Delta<K> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut st = serializer.serialize_struct("Delta", 2)?;
+        st.serialize_field("changed", &self.changed)?;
+        st.serialize_field("removed", &self.removed)?;
+        st.end()
+    }
+}
+
+/// Serialize [`BevyObject`]s changed since `since` and keys of objects despawned since `since`.
+pub(crate) fn save_delta<T, K, S>(
+    world: &mut World,
+    since: Tick,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: BevyObject,
+    K: Component + Eq + Hash + Clone + Serialize,
+    S: Serializer,
+{
+    let human_readable = serializer.is_human_readable();
+    track_delta::<T, K>(world);
+    record_removals(world);
+    let this_run = world.read_change_tick();
+    let mut query = world.query_filtered::<(Entity, &K), (T::Filter, With<K>)>();
+    let entities: Vec<_> = query
+        .iter(world)
+        .map(|(entity, key)| (entity, key.clone()))
+        .collect();
+    let current: FxHashSet<&K> = entities.iter().map(|(_, k)| k).collect();
+    let mut tracker = world.resource_mut::<DeltaTracker>();
+    let mut removed = Vec::new();
+    if let Some(tombstones) = tracker.tombstones_mut::<K>(T::name()) {
+        // Tombstones not newer than `since` cannot be saved again by later deltas.
+        tombstones.retain(|k, tick| tick.is_newer_than(since, this_run) && !current.contains(k));
+        removed.extend(tombstones.keys().cloned());
+    }
+    let tracker = world.resource::<DeltaTracker>();
+    let candidates: Vec<(Entity, bool)> = entities
+        .iter()
+        .filter_map(|(entity, _)| {
+            if structure_changed(world, tracker, *entity, since, this_run) {
+                Some((*entity, true))
+            } else if tree_changed(world, *entity, since, this_run) {
+                Some((*entity, false))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let mut changed = Vec::new();
+    for (entity, structure) in candidates {
+        let (value, is_changed) = changed_scope(since, this_run, || {
+            ser_scope(world, || {
+                entity_scope(entity, || Value::from_serialize(&T::init(), human_readable))
+            })
+        });
+        let value = value.map_err(serde::ser::Error::custom)?;
+        if is_changed || structure {
+            changed.push(value);
+        }
+    }
+    Delta { changed, removed }.serialize(serializer)
+}
+
+/// Deserialize a delta saved by `save_delta`.
+pub(crate) struct DeserializeDelta<T, K>(pub(crate) PhantomData<(T, K)>);
+
+impl<'de, T, K> Deserialize<'de> for DeserializeDelta<T, K>
+where
+    T: BevyObject,
+    K: Component + Eq + Hash + Clone + DeserializeOwned,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("Delta", FIELDS, DeserializeDelta(PhantomData))
+    }
+}
+
+impl<T: BevyObject, K: Component + Eq + Hash> DeserializeDelta<T, K> {
+    fn despawn<E: serde::de::Error>(removed: Vec<K>) -> Result<Self, E> {
+        let removed: FxHashSet<K> = removed.into_iter().collect();
+        with_world_mut(|world| {
+            let mut query = world.query_filtered::<(Entity, &K), T::Filter>();
+            let queue: Vec<_> = query
+                .iter(world)
+                .filter(|(_, key)| removed.contains(key))
+                .map(|(entity, _)| entity)
+                .collect();
+            for entity in queue {
+                safe_despawn(world, entity);
+            }
+        })
+        .map_err(serde::de::Error::custom)?;
+        Ok(DeserializeDelta(PhantomData))
+    }
+}
+
+impl<'de, T, K> Visitor<'de> for DeserializeDelta<T, K>
+where
+    T: BevyObject,
+    K: Component + Eq + Hash + Clone + DeserializeOwned,
+{
+    type Value = Self;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a delta save")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self, A::Error> {
+        if seq.next_element::<MergeRoot<T, K>>()?.is_none() {
+            return Err(serde::de::Error::invalid_length(0, &self));
+        }
+        let Some(removed) = seq.next_element::<Vec<K>>()? else {
+            return Err(serde::de::Error::invalid_length(1, &self));
+        };
+        Self::despawn(removed)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self, A::Error> {
+        let mut removed = Vec::new();
+        while let Some(key) = map.next_key::<Cow<str>>()? {
+            match key.as_ref() {
+                "changed" => {
+                    map.next_value::<MergeRoot<T, K>>()?;
+                }
+                "removed" => removed = map.next_value::<Vec<K>>()?,
+                field => return Err(serde::de::Error::unknown_field(field, FIELDS)),
+            }
+        }
+        Self::despawn(removed)
+    }
+}
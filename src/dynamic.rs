@@ -0,0 +1,299 @@
+//! Module for batches constructed at runtime.
+use std::{borrow::Cow, cell::RefCell, fmt::Debug, marker::PhantomData};
+
+use bevy_ecs::{entity::Entity, system::Resource, world::World};
+use bevy_serde_lens_core::with_world_mut;
+use serde::{
+    de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor},
+    ser::{SerializeMap, SerializeTuple},
+    Deserialize, Deserializer, Serializer,
+};
+
+use crate::{
+    batch::SerializeWorldLens, journal, BatchSerialization, SectionPolicy, SerializeWorld, ZstInit,
+};
+
+type LensFn = for<'t> fn(&'t mut World) -> Box<dyn erased_serde::Serialize + 't>;
+type DeserializeFn = fn(&mut dyn erased_serde::Deserializer) -> Result<(), erased_serde::Error>;
+
+/// Type-erased functions of a [`SerializeWorld`] type.
+#[derive(Clone, Copy)]
+struct DynamicEntry {
+    name: &'static str,
+    is_entities: bool,
+    lens: LensFn,
+    deserialize: DeserializeFn,
+    despawn: fn(&mut World),
+    bound_entities: fn(&mut World) -> Vec<Entity>,
+}
+
+impl DynamicEntry {
+    fn new<T: SerializeWorld + 'static>() -> Self {
+        DynamicEntry {
+            name: T::name(),
+            is_entities: T::IS_ENTITIES,
+            lens: |world| {
+                Box::new(SerializeWorldLens {
+                    world: RefCell::new(world),
+                    p: PhantomData::<T>,
+                })
+            },
+            deserialize: |de| erased_serde::deserialize::<T::De>(de).map(|_| ()),
+            despawn: T::despawn,
+            bound_entities: T::bound_entities,
+        }
+    }
+}
+
+/// A [`BatchSerialization`] type with sections registered at runtime, i.e. by mods.
+///
+/// Insert this as a resource, then use `DynamicBatch` like a static batch.
+/// Sections are saved in registration order.
+///
+/// # Example
+/// ```
+/// world.insert_resource(DynamicBatch::new().with::<Character>().with::<SerializeResource<Gold>>());
+/// world.save::<DynamicBatch, _>(serializer)
+/// world.load::<DynamicBatch, _>(deserializer)
+/// world.despawn_bound_objects::<DynamicBatch>()
+/// ```
+///
+/// # Note
+///
+/// Since sections are only known with world access,
+/// `DynamicBatch` cannot be used in [`batch!`](crate::batch!) and
+/// [`IncrementalLoad`](crate::IncrementalLoad) loads each section in a single step.
+/// [`Positional<DynamicBatch>`](crate::Positional) uses the registered types as its tuple.
+#[derive(Resource, Default, Clone)]
+pub struct DynamicBatch {
+    entries: Vec<DynamicEntry>,
+}
+
+impl Debug for DynamicBatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|x| x.name))
+            .finish()
+    }
+}
+
+impl DynamicBatch {
+    /// Create an empty [`DynamicBatch`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a [`SerializeWorld`] type, replacing a registered type with the same name.
+    pub fn register<T: SerializeWorld + 'static>(&mut self) -> &mut Self {
+        let entry = DynamicEntry::new::<T>();
+        match self.entries.iter_mut().find(|x| x.name == entry.name) {
+            Some(item) => *item = entry,
+            None => self.entries.push(entry),
+        }
+        self
+    }
+
+    /// Register a [`SerializeWorld`] type, replacing a registered type with the same name.
+    pub fn with<T: SerializeWorld + 'static>(mut self) -> Self {
+        self.register::<T>();
+        self
+    }
+
+    /// Remove a registered type by name, returns true if found.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|x| x.name != name);
+        self.entries.len() != len
+    }
+
+    /// Returns true if a type with this name is registered.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.iter().any(|x| x.name == name)
+    }
+
+    /// Returns the number of registered types.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no types are registered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn entries(world: &World) -> Vec<DynamicEntry> {
+        world
+            .get_resource::<DynamicBatch>()
+            .map(|x| x.entries.clone())
+            .unwrap_or_default()
+    }
+
+    /// Obtain registered types on the active world, empty if not in a `load` scope.
+    fn active_entries() -> Vec<DynamicEntry> {
+        with_world_mut(|world| Self::entries(world)).unwrap_or_default()
+    }
+}
+
+impl BatchSerialization for DynamicBatch {
+    type De = DeserializeDynamic;
+    const LEN: usize = 0;
+
+    fn len(world: &mut World) -> usize {
+        world.get_resource::<DynamicBatch>().map_or(0, |x| x.len())
+    }
+
+    /// Names of registered types, empty if not in a `load` scope.
+    fn names() -> Vec<&'static str> {
+        Self::active_entries().iter().map(|x| x.name).collect()
+    }
+
+    /// Always false if not in a `load` scope.
+    fn is_entities(name: &str) -> bool {
+        Self::active_entries()
+            .iter()
+            .any(|x| x.name == name && x.is_entities)
+    }
+
+    fn despawn(world: &mut World) {
+        for entry in Self::entries(world) {
+            (entry.despawn)(world)
+        }
+    }
+
+    fn bound_entities(world: &mut World) -> Vec<Entity> {
+        Self::entries(world)
+            .into_iter()
+            .flat_map(|entry| (entry.bound_entities)(world))
+            .collect()
+    }
+
+    fn despawn_missing(world: &mut World, sections: &[&'static str]) {
+        for entry in Self::entries(world) {
+            if !entry.is_entities && !sections.contains(&entry.name) {
+                (entry.despawn)(world)
+            }
+        }
+    }
+
+    fn serialize<S: Serializer>(world: &mut World, s: S) -> Result<S::Ok, S::Error> {
+        let mut map = s.serialize_map(Some(<Self as BatchSerialization>::len(world)))?;
+        Self::save_map(&mut map, world)?;
+        map.end()
+    }
+
+    fn save_map<S: SerializeMap>(serializer: &mut S, world: &mut World) -> Result<(), S::Error> {
+        for entry in Self::entries(world) {
+            serializer.serialize_entry(entry.name, &*(entry.lens)(world))?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_map<'de, M>(name: &str, map: &mut M) -> Result<(), M::Error>
+    where
+        M: MapAccess<'de>,
+    {
+        let entries =
+            with_world_mut(|world| Self::entries(world)).map_err(serde::de::Error::custom)?;
+        match entries.iter().find(|x| x.name == name) {
+            Some(entry) => {
+                journal::record_section(entry.name);
+                map.next_value_seed(DeserializeFnSeed(entry.deserialize))
+            }
+            None => Err(serde::de::Error::custom(format!(
+                "Unknown type name {name}."
+            ))),
+        }
+    }
+
+    fn save_tuple<S: SerializeTuple>(
+        serializer: &mut S,
+        world: &mut World,
+    ) -> Result<(), S::Error> {
+        for entry in Self::entries(world) {
+            serializer.serialize_element(&*(entry.lens)(world))?;
+        }
+        Ok(())
+    }
+
+    fn deserialize_tuple<'de, A>(seq: &mut A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let entries =
+            with_world_mut(|world| Self::entries(world)).map_err(serde::de::Error::custom)?;
+        for entry in entries {
+            journal::record_section(entry.name);
+            if seq
+                .next_element_seed(DeserializeFnSeed(entry.deserialize))?
+                .is_none()
+            {
+                return Err(serde::de::Error::custom(format!(
+                    "Missing section {}.",
+                    entry.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+struct DeserializeFnSeed(DeserializeFn);
+
+impl<'de> DeserializeSeed<'de> for DeserializeFnSeed {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        (self.0)(&mut <dyn erased_serde::Deserializer>::erase(deserializer))
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Deserialize a [`DynamicBatch`].
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct DeserializeDynamic(());
+
+impl ZstInit for DeserializeDynamic {
+    fn init() -> Self {
+        DeserializeDynamic(())
+    }
+}
+
+impl<'de> Deserialize<'de> for DeserializeDynamic {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(DeserializeDynamic(()))
+    }
+}
+
+impl<'de> Visitor<'de> for DeserializeDynamic {
+    type Value = DeserializeDynamic;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("map of types")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+        let entries = with_world_mut(|world| DynamicBatch::entries(world))
+            .map_err(serde::de::Error::custom)?;
+        let mut found = Vec::new();
+        while let Some(key) = map.next_key::<Cow<str>>()? {
+            let Some(entry) = entries.iter().find(|x| x.name == key.as_ref()) else {
+                SectionPolicy::unknown::<M::Error>(&key)?;
+                map.next_value::<IgnoredAny>()?;
+                continue;
+            };
+            found.push(entry.name);
+            journal::record_section(entry.name);
+            map.next_value_seed(DeserializeFnSeed(entry.deserialize))?;
+        }
+        if SectionPolicy::get::<M::Error>()?.deny_missing {
+            if let Some(entry) = entries.iter().find(|x| !found.contains(&x.name)) {
+                return Err(serde::de::Error::custom(format!(
+                    "Missing section {}.",
+                    entry.name
+                )));
+            }
+        }
+        Ok(DeserializeDynamic(()))
+    }
+}
@@ -1,51 +1,686 @@
 //! Module for serializing [`Entity`] and hierarchy.
-use std::cell::RefCell;
+use std::{
+    any::TypeId,
+    cell::RefCell,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
 
-use bevy_ecs::{entity::Entity, query::With};
-use bevy_hierarchy::{BuildWorldChildren, Parent};
-use bevy_serde_lens_core::current_entity;
+use bevy_ecs::{
+    component::Component,
+    entity::{Entity, EntityMapper, MapEntities},
+    query::With,
+    system::Resource,
+    world::{EntityWorldMut, World},
+};
+use bevy_hierarchy::{BuildWorldChildren, Children, Parent};
+use bevy_serde_lens_core::{current_entity, with_world, with_world_mut};
 use ref_cast::RefCast;
-use rustc_hash::FxHashMap;
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
+use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{
-    world_entity_scope, world_entity_scope_mut, BindProject, BindProjectQuery, Maybe, ZstInit,
+    extractors::ParentKeys, journal, merge, world_entity_scope, world_entity_scope_mut,
+    BindProject, BindProjectQuery, Maybe, ZstInit,
 };
 
 thread_local! {
     pub(crate) static EID_MAP: RefCell<FxHashMap<u64, Entity>> = RefCell::new(FxHashMap::default());
+    /// Map of [`PersistentId`](crate::PersistentId)s deserialized in the current batch.
+    pub(crate) static PERSISTENT_MAP: RefCell<FxHashMap<u64, Entity>> = RefCell::new(FxHashMap::default());
+    static DEFERRED: RefCell<Option<Deferred>> = const { RefCell::new(None) };
+    static MAPPED: RefCell<Option<Mapped>> = const { RefCell::new(None) };
+    static SAVE: RefCell<Option<SaveState>> = const { RefCell::new(None) };
+    static LABELS: RefCell<FxHashMap<u64, String>> = RefCell::new(FxHashMap::default());
+    /// Keys in [`EID_MAP`] and [`PERSISTENT_MAP`] of each entity, used by [`remap`].
+    static REVERSE: RefCell<FxHashMap<Entity, Vec<RefKey>>> = RefCell::new(FxHashMap::default());
 }
 
-#[doc(hidden)]
-#[cfg(any(feature = "extra-checks", debug_assertions))]
-macro_rules! validate {
-    ($val: expr) => {{
-        let val = $val;
-        if EID_MAP.with(|x| x.borrow().contains_key(&val)) {
-            return Err(serde::ser::Error::custom("EID not serialized"));
-        }
-        val
-    }};
-    (@$val: expr) => {{
-        let val = $val;
-        if let Some(val) = val {
-            if EID_MAP.with(|x| x.borrow().contains_key(&val)) {
-                return Err(serde::ser::Error::custom("EID not serialized"));
+/// Resource that controls how [`EntityPtr`], [`OptionEntityPtr`] and [`Parented`]
+/// are resolved during `load`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource)]
+pub enum EntityRefMode {
+    /// The referenced [`EntityId`] must be deserialized earlier in the batch.
+    #[default]
+    Immediate,
+    /// References to an [`EntityId`] not yet deserialized are resolved
+    /// after the whole batch is loaded, this allows cyclic references.
+    ///
+    /// Errors if an [`EntityId`] is never deserialized.
+    /// Only applies to `load`, `load_versioned` and `load_replace`.
+    Deferred,
+}
+
+/// Resource that controls how [`EntityId`]s are numbered during `save` and `load`.
+///
+/// Must be the same on `save` and `load`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource)]
+pub enum EntityNumbering {
+    /// Save [`Entity::to_bits`], which changes every session.
+    #[default]
+    Bits,
+    /// Save dense indices `0..n` assigned in serialization order,
+    /// identical worlds produce identical saves.
+    ///
+    /// Dense indices share the id space of [`EntityResolver`]s,
+    /// saving an entity resolved by an [`EntityResolver`] is an error in this mode
+    /// and resolvers are not used on load.
+    /// Saving or loading components registered with `register_map_entities` is an error in this mode.
+    Dense,
+}
+
+/// Tag of internal keys of dense indices, avoids collision with other ids in the [`EntityId`] map.
+const DENSE_TAG: u64 = 1 << 63;
+
+/// Resource that controls how [`EntityPtr`], [`OptionEntityPtr`] and [`Parented`]
+/// are saved if the referenced entity is not part of the save.
+///
+/// An entity is part of the save if its [`EntityId`] is saved earlier,
+/// or anywhere in the save if [`EntityRefMode`] is deferred,
+/// or if it is resolved by an [`EntityResolver`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource)]
+pub enum DanglingPolicy {
+    /// Fail the save.
+    #[default]
+    Error,
+    /// Save `None` for [`OptionEntityPtr`] and `Maybe<Parented>`, fail the save otherwise.
+    Null,
+    /// Omit the component containing the reference if it is in a [`Maybe`],
+    /// fail the save otherwise.
+    DropComponent,
+}
+
+/// State of a `save`.
+struct SaveState {
+    /// Dense indices of entities if [`EntityNumbering`] is dense.
+    dense: Option<FxHashMap<Entity, u64>>,
+    /// Entities whose [`EntityId`] is saved.
+    saved: FxHashSet<Entity>,
+    policy: DanglingPolicy,
+    /// If set, collecting [`EntityId`]s before the actual save.
+    collecting: bool,
+    /// Set in a scope that omits components with dangling references.
+    dangling: Option<bool>,
+}
+
+/// Run a save function with ids numbered according to [`EntityNumbering`]
+/// and references checked according to [`DanglingPolicy`].
+///
+/// If [`EntityRefMode`] is deferred, run `collect` first to find all saved [`EntityId`]s.
+pub(crate) fn save_scope<T>(
+    world: &mut World,
+    collect: impl FnOnce(&mut World),
+    f: impl FnOnce(&mut World) -> T,
+) -> T {
+    let dense = world.get_resource::<EntityNumbering>() == Some(&EntityNumbering::Dense);
+    let deferred = world.get_resource::<EntityRefMode>() == Some(&EntityRefMode::Deferred);
+    let state = SaveState {
+        dense: dense.then(FxHashMap::default),
+        saved: FxHashSet::default(),
+        policy: world
+            .get_resource::<DanglingPolicy>()
+            .copied()
+            .unwrap_or_default(),
+        collecting: deferred,
+        dangling: None,
+    };
+    let previous = SAVE.with(|s| s.replace(Some(state)));
+    if deferred {
+        collect(world);
+        SAVE.with(|s| {
+            if let Some(s) = s.borrow_mut().as_mut() {
+                s.collecting = false;
             }
+        });
+    }
+    let result = f(world);
+    SAVE.with(|s| s.replace(previous));
+    result
+}
+
+/// Obtain the serialized id of an [`Entity`].
+pub(crate) fn save_id(entity: Entity) -> u64 {
+    SAVE.with(
+        |s| match s.borrow_mut().as_mut().and_then(|s| s.dense.as_mut()) {
+            Some(map) => {
+                let len = map.len() as u64;
+                *map.entry(entity).or_insert(len)
+            }
+            None => entity.to_bits(),
+        },
+    )
+}
+
+/// Returns true if the current `save` uses [`EntityNumbering::Dense`].
+fn is_dense_save() -> bool {
+    SAVE.with(|s| s.borrow().as_ref().is_some_and(|s| s.dense.is_some()))
+}
+
+/// Obtain the serialized id of an [`Entity`] whose [`EntityId`] is saved.
+fn save_entity_id(entity: Entity) -> u64 {
+    SAVE.with(|s| {
+        if let Some(s) = s.borrow_mut().as_mut() {
+            s.saved.insert(entity);
         }
-        val
-    }};
+    });
+    save_id(entity)
+}
+
+/// Error of a reference to an entity not part of the save.
+fn dangling_error<E: serde::ser::Error>(entity: Entity) -> E {
+    E::custom(format!("Entity {entity:?} is not part of the save."))
+}
+
+/// Obtain the serialized id of a referenced [`Entity`].
+fn save_ref<E: serde::ser::Error>(entity: Entity) -> Result<u64, E> {
+    try_save_ref(entity, false)?.ok_or_else(|| dangling_error(entity))
 }
 
-#[doc(hidden)]
-#[cfg(not(any(feature = "extra-checks", debug_assertions)))]
-macro_rules! validate {
-    ($val: expr) => {
-        $val
+/// Obtain the serialized id of a referenced [`Entity`],
+/// returns `None` if it should be saved as `None` according to [`DanglingPolicy`].
+fn try_save_ref<E: serde::ser::Error>(entity: Entity, nullable: bool) -> Result<Option<u64>, E> {
+    if let Some(id) = resolve_save(entity) {
+        if is_dense_save() {
+            return Err(E::custom(format!(
+                "Entity {entity:?} is resolved by an EntityResolver, \
+                which is not supported with EntityNumbering::Dense."
+            )));
+        }
+        return Ok(Some(id));
+    }
+    let dangling = SAVE.with(|s| {
+        let mut s = s.borrow_mut();
+        let s = s.as_mut()?;
+        if s.collecting || s.saved.contains(&entity) {
+            return None;
+        }
+        if let (DanglingPolicy::DropComponent, Some(dangling)) = (s.policy, s.dangling.as_mut()) {
+            *dangling = true;
+            return None;
+        }
+        Some(s.policy)
+    });
+    match dangling {
+        None => Ok(Some(save_id(entity))),
+        Some(DanglingPolicy::Null) if nullable => Ok(None),
+        Some(_) => Err(dangling_error(entity)),
+    }
+}
+
+/// Obtain the serialized id of an optional referenced [`Entity`].
+fn save_option_ref<E: serde::ser::Error>(entity: Option<Entity>) -> Result<Option<u64>, E> {
+    Ok(match entity {
+        Some(entity) => try_save_ref(entity, true)?,
+        None => None,
+    })
+}
+
+/// Returns true if components with dangling references should be omitted.
+pub(crate) fn drop_dangling() -> bool {
+    SAVE.with(|s| {
+        s.borrow()
+            .as_ref()
+            .is_some_and(|s| !s.collecting && s.policy == DanglingPolicy::DropComponent)
+    })
+}
+
+/// Run a function and returns if it contains dangling references.
+pub(crate) fn dangling_scope<T>(f: impl FnOnce() -> T) -> (T, bool) {
+    let previous = SAVE.with(|s| {
+        s.borrow_mut()
+            .as_mut()
+            .and_then(|s| s.dangling.replace(false))
+    });
+    let result = f();
+    let dangling = SAVE.with(|s| {
+        s.borrow_mut()
+            .as_mut()
+            .and_then(|s| std::mem::replace(&mut s.dangling, previous))
+    });
+    (result, dangling == Some(true))
+}
+
+/// Obtain the key of a serialized id in the [`EntityId`] map.
+fn load_key(id: u64) -> u64 {
+    let dense = with_world_mut(|world| {
+        world.get_resource::<EntityNumbering>() == Some(&EntityNumbering::Dense)
+    })
+    .unwrap_or(false);
+    if dense {
+        id | DENSE_TAG
+    } else {
+        id
+    }
+}
+
+/// Obtain the serialized id of a key in the [`EntityId`] map.
+pub(crate) fn file_id(key: u64) -> u64 {
+    key & !DENSE_TAG
+}
+
+/// Tag of internal keys of labels, avoids collision with other ids in the [`EntityId`] map.
+const LABEL_TAG: u64 = 3 << 62;
+
+/// Obtain the key of a label in the [`EntityId`] map.
+fn label_key(label: &str) -> u64 {
+    let mut hasher = FxHasher::default();
+    label.hash(&mut hasher);
+    let key = hasher.finish() | LABEL_TAG;
+    LABELS.with(|x| {
+        x.borrow_mut()
+            .entry(key)
+            .or_insert_with(|| label.to_owned());
+    });
+    key
+}
+
+/// Obtain the label of a key in the [`EntityId`] map.
+pub(crate) fn get_label(key: u64) -> Option<String> {
+    if key & LABEL_TAG != LABEL_TAG {
+        return None;
+    }
+    LABELS.with(|x| x.borrow().get(&key).cloned())
+}
+
+/// Describe a key in the [`EntityId`] map as it is serialized.
+fn describe(key: u64) -> String {
+    match get_label(key) {
+        Some(label) => format!("{label:?}"),
+        None => file_id(key).to_string(),
+    }
+}
+
+/// Clear the [`EntityId`] map before a `load`.
+pub(crate) fn clear_ids() {
+    EID_MAP.with(|m| m.borrow_mut().clear());
+    PERSISTENT_MAP.with(|m| m.borrow_mut().clear());
+    LABELS.with(|m| m.borrow_mut().clear());
+    REVERSE.with(|m| m.borrow_mut().clear());
+    crate::clear_parent_keys();
+}
+
+/// Ids deserialized in a `load`, kept between steps of an incremental load.
+#[derive(Default)]
+pub(crate) struct IdMaps {
+    ids: FxHashMap<u64, Entity>,
+    persistent: FxHashMap<u64, Entity>,
+    labels: FxHashMap<u64, String>,
+    reverse: FxHashMap<Entity, Vec<RefKey>>,
+    parents: ParentKeys,
+}
+
+impl IdMaps {
+    /// Swap with the ids of the current `load`.
+    pub(crate) fn swap(&mut self) {
+        EID_MAP.with(|m| std::mem::swap(&mut *m.borrow_mut(), &mut self.ids));
+        PERSISTENT_MAP.with(|m| std::mem::swap(&mut *m.borrow_mut(), &mut self.persistent));
+        LABELS.with(|m| std::mem::swap(&mut *m.borrow_mut(), &mut self.labels));
+        REVERSE.with(|m| std::mem::swap(&mut *m.borrow_mut(), &mut self.reverse));
+        crate::swap_parent_keys(&mut self.parents);
+    }
+}
+
+/// Associate a deserialized id with an entity.
+pub(crate) fn insert_id(key: RefKey, entity: Entity) {
+    let previous = match key {
+        RefKey::EntityId(key) => EID_MAP.with(|m| m.borrow_mut().insert(key, entity)),
+        RefKey::Persistent(id) => PERSISTENT_MAP.with(|m| m.borrow_mut().insert(id, entity)),
     };
-    (@$val: expr) => {
-        $val
+    REVERSE.with(|m| {
+        let mut m = m.borrow_mut();
+        if let Some(keys) = previous.and_then(|previous| m.get_mut(&previous)) {
+            keys.retain(|x| *x != key);
+        }
+        m.entry(entity).or_default().push(key);
+    });
+}
+
+/// Point ids deserialized as `from` and components of `from` pending remapping to `to`,
+/// used when `from` is merged into `to`.
+pub(crate) fn remap(from: Entity, to: Entity) {
+    let keys = REVERSE.with(|m| m.borrow_mut().remove(&from));
+    for key in keys.into_iter().flatten() {
+        insert_id(key, to);
+    }
+    MAPPED.with(|m| {
+        if let Some(m) = m.borrow_mut().as_mut() {
+            m.moved.insert(from, to);
+        }
+    });
+}
+
+/// Key of a serialized [`EntityId`] in the [`EntityId`] map.
+///
+/// Accepts string labels in human readable formats, i.e. `"boss_door"`.
+struct Key(u64);
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(KeyVisitor)
+        } else {
+            u64::deserialize(deserializer).map(|id| Key(load_key(id)))
+        }
+    }
+}
+
+struct KeyVisitor;
+
+impl Visitor<'_> for KeyVisitor {
+    type Value = Key;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an entity id or label")
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(Key(load_key(v)))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        match u64::try_from(v) {
+            Ok(v) => self.visit_u64(v),
+            Err(_) => Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Signed(v),
+                &self,
+            )),
+        }
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Key(label_key(v)))
+    }
+}
+
+/// Id of a referenced entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum RefKey {
+    /// Key in the [`EntityId`] map.
+    EntityId(u64),
+    /// A [`PersistentId`](crate::PersistentId).
+    Persistent(u64),
+}
+
+impl RefKey {
+    /// Describe the key as it is serialized.
+    fn describe(&self) -> String {
+        match self {
+            RefKey::EntityId(key) => format!("Entity {}", describe(*key)),
+            RefKey::Persistent(id) => format!("PersistentId {id}"),
+        }
+    }
+}
+
+/// State of deferred references in a `load`.
+#[derive(Default)]
+struct Deferred {
+    /// Placeholder entities of ids referenced but not yet deserialized.
+    placeholders: FxHashMap<RefKey, Entity>,
+    /// Entities whose [`EntityId`] was referenced before deserialization and their placeholders.
+    pending: Vec<(Entity, Entity)>,
+}
+
+/// Returns true if references are deferred.
+pub(crate) fn is_deferred() -> bool {
+    DEFERRED.with(|d| d.borrow().is_some())
+}
+
+/// Returns true if `entity` is waiting to be merged into a placeholder.
+pub(crate) fn is_pending(entity: Entity) -> bool {
+    DEFERRED.with(|d| {
+        d.borrow()
+            .as_ref()
+            .is_some_and(|d| d.pending.iter().any(|(x, _)| *x == entity))
+    })
+}
+
+/// Run a load function with deferred references if `deferred` is set,
+/// then merge entities into placeholders referenced before their [`EntityId`]s were deserialized.
+pub(crate) fn deferred_scope<T, E: serde::de::Error>(
+    world: &mut World,
+    deferred: bool,
+    f: impl FnOnce(&mut World) -> Result<T, E>,
+) -> Result<T, E> {
+    if !deferred {
+        return f(world);
+    }
+    let previous = DEFERRED.with(|d| d.replace(Some(Deferred::default())));
+    let result = f(world);
+    let state = DEFERRED.with(|d| d.replace(previous)).unwrap_or_default();
+    let result = result.and_then(|result| {
+        for (entity, placeholder) in state.pending {
+            // Keep children parented to the placeholder by `Parented`.
+            if let Some(children) = world.get::<Children>(placeholder).map(|x| x.to_vec()) {
+                world.entity_mut(entity).push_children(&children);
+            }
+            merge::transfer(world, entity, placeholder);
+            journal::record_replace(entity, placeholder);
+        }
+        match state.placeholders.keys().next() {
+            Some(key) => Err(serde::de::Error::custom(format!(
+                "{} not serialized.",
+                key.describe()
+            ))),
+            None => Ok(result),
+        }
+    });
+    merge::clear();
+    result
+}
+
+/// Resolves references to entities outside of a batch, like the player or persistent managers.
+///
+/// Used by [`EntityPtr`] and [`OptionEntityPtr`],
+/// register with `register_entity_resolver`.
+pub trait EntityResolver: Send + Sync + 'static {
+    /// Returns the id to save an entity as if it is resolved by this resolver.
+    ///
+    /// Should return `None` for entities in the batch, ids less than `2^32`
+    /// never collide with ids of entities in the batch.
+    /// Not supported with [`EntityNumbering::Dense`].
+    fn save(&self, world: &World, entity: Entity) -> Option<u64>;
+    /// Returns the entity of an id not found in the batch.
+    fn load(&self, world: &mut World, id: u64) -> Option<Entity>;
+}
+
+/// Registered [`EntityResolver`]s, tried in order.
+#[derive(Resource, Default, Clone)]
+pub(crate) struct EntityResolvers(pub(crate) Vec<Arc<dyn EntityResolver>>);
+
+/// Find the id of an entity outside of the batch.
+fn resolve_save(entity: Entity) -> Option<u64> {
+    with_world(|world| {
+        world
+            .get_resource::<EntityResolvers>()?
+            .0
+            .iter()
+            .find_map(|x| x.save(world, entity))
+    })
+    .ok()
+    .flatten()
+}
+
+/// Find the entity of an id outside of the batch.
+fn resolve_load(id: u64) -> Option<Entity> {
+    with_world_mut(|world| resolve_with(world, id))
+        .ok()
+        .flatten()
+}
+
+/// Find the entity of an id outside of the batch.
+fn resolve_with(world: &mut World, id: u64) -> Option<Entity> {
+    let resolvers = world.get_resource::<EntityResolvers>()?.0.clone();
+    resolvers.iter().find_map(|x| x.load(world, id))
+}
+
+type MapEntitiesFn = fn(&mut World, Entity, &mut Mapper);
+
+/// Components registered to be remapped via [`MapEntities`] after `load`.
+#[derive(Resource, Default)]
+pub(crate) struct EntityMappings(FxHashMap<TypeId, MapEntitiesFn>);
+
+impl EntityMappings {
+    pub(crate) fn register<T: Component + MapEntities>(&mut self) {
+        self.0.insert(TypeId::of::<T>(), |world, entity, mapper| {
+            if let Some(mut component) = world.get_mut::<T>(entity) {
+                component.map_entities(mapper)
+            }
+        });
+    }
+}
+
+/// [`EntityMapper`] that either collects or remaps entities.
+pub(crate) enum Mapper<'t> {
+    Collect(&'t mut Vec<Entity>),
+    Map(&'t FxHashMap<Entity, Entity>),
+}
+
+impl EntityMapper for Mapper<'_> {
+    fn map_entity(&mut self, entity: Entity) -> Entity {
+        match self {
+            Mapper::Collect(entities) => {
+                entities.push(entity);
+                entity
+            }
+            Mapper::Map(map) => map.get(&entity).copied().unwrap_or(entity),
+        }
+    }
+}
+
+/// Components pending remapping in a `load`.
+#[derive(Default)]
+struct Mapped {
+    pending: Vec<(Entity, MapEntitiesFn)>,
+    /// Entities merged into other entities, with their components.
+    moved: FxHashMap<Entity, Entity>,
+}
+
+impl Mapped {
+    /// Obtain the entity a component recorded on `entity` is on.
+    fn resolve(&self, mut entity: Entity) -> Entity {
+        while let Some(to) = self.moved.get(&entity) {
+            entity = *to;
+        }
+        entity
+    }
+}
+
+/// Record component `T` on an entity to be remapped if registered.
+pub(crate) fn record_map_entities<T: Component>(world: &World, entity: Entity) {
+    let Some(f) = world
+        .get_resource::<EntityMappings>()
+        .and_then(|x| x.0.get(&TypeId::of::<T>()).copied())
+    else {
+        return;
     };
+    MAPPED.with(|m| {
+        if let Some(m) = m.borrow_mut().as_mut() {
+            m.pending.push((entity, f))
+        }
+    })
+}
+
+/// Error if component `T` is registered to be remapped and the current `save`
+/// uses [`EntityNumbering::Dense`], since its entities are saved as [`Entity::to_bits`].
+pub(crate) fn check_map_entities<T: Component, E: serde::ser::Error>(
+    world: &World,
+) -> Result<(), E> {
+    if is_dense_save()
+        && world
+            .get_resource::<EntityMappings>()
+            .is_some_and(|x| x.0.contains_key(&TypeId::of::<T>()))
+    {
+        return Err(E::custom(
+            "Components registered with `register_map_entities` \
+            are not supported with EntityNumbering::Dense.",
+        ));
+    }
+    Ok(())
+}
+
+/// Run a load function, then remap entities of components registered with `register_map_entities`
+/// from serialized [`EntityId`]s to loaded entities.
+///
+/// Must be called in the `load` scope.
+pub(crate) fn map_entities_scope<T, E: serde::de::Error>(
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    let previous = MAPPED.with(|m| m.replace(Some(Mapped::default())));
+    let result = f();
+    let state = MAPPED.with(|m| m.replace(previous)).unwrap_or_default();
+    let result = result?;
+    if state.pending.is_empty() {
+        return Ok(result);
+    }
+    with_world_mut(|world| {
+        if world.get_resource::<EntityNumbering>() == Some(&EntityNumbering::Dense) {
+            return Err(serde::de::Error::custom(
+                "Components registered with `register_map_entities` \
+                are not supported with EntityNumbering::Dense.",
+            ));
+        }
+        for (entity, map) in &state.pending {
+            let entity = state.resolve(*entity);
+            let mut entities = Vec::new();
+            map(world, entity, &mut Mapper::Collect(&mut entities));
+            let mut mapped = FxHashMap::default();
+            for item in entities {
+                if item == Entity::PLACEHOLDER {
+                    continue;
+                }
+                let id = item.to_bits();
+                let Some(target) = EID_MAP
+                    .with(|x| x.borrow().get(&id).copied())
+                    .or_else(|| resolve_with(world, id))
+                else {
+                    return Err(serde::de::Error::custom(format!(
+                        "Entity {id} not serialized."
+                    )));
+                };
+                mapped.insert(item, target);
+            }
+            map(world, entity, &mut Mapper::Map(&mapped));
+        }
+        Ok(result)
+    })
+    .map_err(serde::de::Error::custom)?
+}
+
+/// Obtain a placeholder entity for an id not yet deserialized.
+pub(crate) fn placeholder(id: RefKey) -> Option<Entity> {
+    if let Some(entity) = DEFERRED.with(|d| {
+        d.borrow()
+            .as_ref()
+            .map(|d| d.placeholders.get(&id).copied())
+    })? {
+        return Some(entity);
+    }
+    let entity = with_world_mut(|world| world.spawn_empty().id()).ok()?;
+    journal::record_undo(move |world| crate::safe_despawn(world, entity));
+    DEFERRED.with(|d| {
+        if let Some(d) = d.borrow_mut().as_mut() {
+            d.placeholders.insert(id, entity);
+        }
+    });
+    Some(entity)
+}
+
+/// Returns the entity an id should be registered as,
+/// which is a placeholder if previously referenced.
+pub(crate) fn register(id: RefKey, entity: Entity) -> Entity {
+    DEFERRED.with(|d| {
+        let mut d = d.borrow_mut();
+        let Some(d) = d.as_mut() else {
+            return entity;
+        };
+        match d.placeholders.remove(&id) {
+            Some(placeholder) => {
+                d.pending.push((entity, placeholder));
+                placeholder
+            }
+            None => entity,
+        }
+    })
 }
 
 /// Obtain the [`Entity`] of a serialized [`EntityId`].
@@ -56,8 +691,9 @@ macro_rules! validate {
 /// * If used outside `bevy_serde_lens`.
 /// * If [`EntityId`] is not serialized in the same batch.
 pub fn validate<'de, S: Deserializer<'de>>(id: u64) -> Result<Entity, S::Error> {
+    let key = load_key(id);
     EID_MAP
-        .with(|x| x.borrow().get(&id).copied())
+        .with(|x| x.borrow().get(&key).copied())
         .ok_or_else(|| serde::de::Error::custom(format!("Entity {id} not serialized.")))
 }
 
@@ -67,11 +703,27 @@ pub fn validate<'de, S: Deserializer<'de>>(id: u64) -> Result<Entity, S::Error>
 ///
 /// * If used outside a deserialize implementation.
 /// * If used outside `bevy_serde_lens`.
-/// * If [`EntityId`] is not serialized in the same batch.
+/// * If [`EntityId`] is not serialized in the same batch, not resolved by an [`EntityResolver`]
+///   and [`EntityRefMode`] is not deferred.
 pub fn get_entity<'de, S: Deserializer<'de>>(id: u64) -> Result<Entity, S::Error> {
+    find_entity(load_key(id))
+}
+
+/// Find the [`Entity`] of a key in the [`EntityId`] map, or via [`EntityResolver`]s.
+pub(crate) fn find_entity<E: serde::de::Error>(key: u64) -> Result<Entity, E> {
     EID_MAP
-        .with(|x| x.borrow().get(&id).copied())
-        .ok_or_else(|| serde::de::Error::custom(format!("Entity {id} not serialized.")))
+        .with(|x| x.borrow().get(&key).copied())
+        .map(merge::redirect)
+        .or_else(|| match get_label(key) {
+            Some(_) => None,
+            // Resolvers are not used with `EntityNumbering::Dense`.
+            None if key & DENSE_TAG != 0 => None,
+            None => resolve_load(file_id(key)),
+        })
+        .or_else(|| placeholder(RefKey::EntityId(key)))
+        .ok_or_else(|| {
+            serde::de::Error::custom(format!("Entity {} not serialized.", describe(key)))
+        })
 }
 
 /// Serialize [`Entity`] as a number for future reference.
@@ -86,30 +738,28 @@ pub struct EntityId;
 impl EntityId {
     /// Serialize with [`EntityId`].
     pub fn serialize<S: Serializer>(item: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
-        item.to_bits().serialize(serializer)
+        save_entity_id(*item).serialize(serializer)
     }
 
     /// Deserialize with [`EntityId`].
     pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
-        let original = u64::deserialize(deserializer)?;
-        EID_MAP
-            .with(|e| e.borrow().get(&original).copied())
-            .ok_or(serde::de::Error::custom("EntityId not serialized."))
+        find_entity(Key::deserialize(deserializer)?.0)
     }
 }
 
 impl Serialize for EntityId {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         let entity = current_entity().map_err(serde::ser::Error::custom)?;
-        entity.to_bits().serialize(serializer)
+        save_entity_id(entity).serialize(serializer)
     }
 }
 
 impl<'de> Deserialize<'de> for EntityId {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let entity = u64::deserialize(deserializer)?;
+        let Key(key) = Key::deserialize(deserializer)?;
         let current = current_entity().map_err(serde::de::Error::custom)?;
-        EID_MAP.with(|x| x.borrow_mut().insert(entity, current));
+        let current = register(RefKey::EntityId(key), current);
+        insert_id(RefKey::EntityId(key), current);
         Ok(EntityId)
     }
 }
@@ -147,17 +797,14 @@ impl Serialize for Parented {
             let Some(component) = entity.get::<Parent>() else {
                 return Err(serde::ser::Error::custom("Parent missing."));
             };
-            validate!(component.get().to_bits()).serialize(serializer)
+            save_ref(component.get())?.serialize(serializer)
         })?
     }
 }
 
 impl<'de> Deserialize<'de> for Parented {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let original = u64::deserialize(deserializer)?;
-        let Some(parent) = EID_MAP.with(|e| e.borrow().get(&original).copied()) else {
-            return Err(serde::de::Error::custom("Parent not serialized."));
-        };
+        let parent = find_entity(Key::deserialize(deserializer)?.0)?;
         world_entity_scope_mut::<_, D>(|world, entity| {
             let Some(mut entity) = world.get_entity_mut(entity) else {
                 return Err(serde::de::Error::custom(format!(
@@ -179,6 +826,10 @@ impl ZstInit for Parented {
 impl BindProject for Parented {
     type To = Self;
     type Filter = With<Parent>;
+
+    fn remove(entity: &mut EntityWorldMut) {
+        entity.remove_parent();
+    }
 }
 
 impl BindProjectQuery for Parented {
@@ -193,19 +844,21 @@ impl Serialize for Maybe<Parented> {
                     "Entity missing: {entity:?}."
                 )));
             };
-            validate!(@entity
-                .get::<Parent>()
-                .map(|x| x.get().to_bits()))
-            .serialize(serializer)
+            save_option_ref(entity.get::<Parent>().map(Parent::get))?.serialize(serializer)
         })?
     }
 }
 
 impl<'de> Deserialize<'de> for Maybe<Parented> {
     fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let original = <Option<u64>>::deserialize(deserializer)?;
-        if let Some(original) = original {
-            let parent = get_entity::<D>(original)?;
+        let original = <Option<Key>>::deserialize(deserializer)?;
+        if original.is_none() {
+            if let Ok(entity) = current_entity() {
+                crate::merge::record_orphan(entity);
+            }
+        }
+        if let Some(Key(original)) = original {
+            let parent = find_entity(original)?;
             world_entity_scope_mut::<_, D>(|world, entity| {
                 if let Some(mut entity) = world.get_entity_mut(entity) {
                     entity.set_parent(parent);
@@ -224,6 +877,10 @@ impl<'de> Deserialize<'de> for Maybe<Parented> {
 impl BindProject for Maybe<Parented> {
     type To = Self;
     type Filter = ();
+
+    fn remove(entity: &mut EntityWorldMut) {
+        entity.remove_parent();
+    }
 }
 
 impl BindProjectQuery for Maybe<Parented> {
@@ -237,6 +894,8 @@ impl BindProjectQuery for Maybe<Parented> {
 /// * On serialization: Save a unique id for this [`Entity`].
 /// * On deserialization: Find the [`Entity`] of a previously serialized [`EntityId`].
 ///
+/// Entities outside of the batch can be resolved by an [`EntityResolver`].
+///
 /// # Errors
 ///
 /// If associated [`EntityId`] was not serialized.
@@ -246,14 +905,14 @@ pub struct EntityPtr(pub Entity);
 
 impl Serialize for EntityPtr {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        validate!(self.0.to_bits()).serialize(serializer)
+        save_ref(self.0)?.serialize(serializer)
     }
 }
 
 impl<'de> Deserialize<'de> for EntityPtr {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let original = <u64>::deserialize(deserializer)?;
-        Ok(EntityPtr(get_entity::<D>(original)?))
+        let Key(key) = Key::deserialize(deserializer)?;
+        Ok(EntityPtr(find_entity(key)?))
     }
 }
 
@@ -264,6 +923,8 @@ impl<'de> Deserialize<'de> for EntityPtr {
 /// * On serialization: Save a unique id for this [`Entity`].
 /// * On deserialization: Find the [`Entity`] of a previously serialized [`EntityId`].
 ///
+/// Entities outside of the batch can be resolved by an [`EntityResolver`].
+///
 /// # Errors
 ///
 /// If associated [`EntityId`] was not serialized.
@@ -273,15 +934,15 @@ pub struct OptionEntityPtr(pub Option<Entity>);
 
 impl Serialize for OptionEntityPtr {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        validate!(@self.0.map(|x| x.to_bits())).serialize(serializer)
+        save_option_ref(self.0)?.serialize(serializer)
     }
 }
 
 impl<'de> Deserialize<'de> for OptionEntityPtr {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let entity = <Option<u64>>::deserialize(deserializer)?;
+        let entity = <Option<Key>>::deserialize(deserializer)?;
         match entity {
-            Some(id) => Ok(OptionEntityPtr(Some(get_entity::<D>(id)?))),
+            Some(Key(key)) => Ok(OptionEntityPtr(Some(find_entity(key)?))),
             None => Ok(OptionEntityPtr(None)),
         }
     }
@@ -317,8 +978,6 @@ impl OptionEntityPtr {
 }
 
 pub(crate) mod query {
-    #[allow(unused)]
-    use super::EID_MAP;
     use bevy_ecs::{entity::Entity, query::QueryData};
     use bevy_hierarchy::Parent;
     use serde::{Serialize, Serializer};
@@ -330,13 +989,13 @@ pub(crate) mod query {
 
     impl Serialize for SerializeEntity {
         fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-            validate!(self.entity.to_bits()).serialize(serializer)
+            super::save_entity_id(self.entity).serialize(serializer)
         }
     }
 
     impl Serialize for SerializeEntityItem<'_> {
         fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-            validate!(self.entity.to_bits()).serialize(serializer)
+            super::save_entity_id(self.entity).serialize(serializer)
         }
     }
 
@@ -347,13 +1006,13 @@ pub(crate) mod query {
 
     impl Serialize for SerializeParent {
         fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-            validate!(self.parent.to_bits()).serialize(serializer)
+            super::save_ref(self.parent.get())?.serialize(serializer)
         }
     }
 
     impl Serialize for SerializeParentItem<'_> {
         fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-            validate!(self.parent.to_bits()).serialize(serializer)
+            super::save_ref(self.parent.get())?.serialize(serializer)
         }
     }
 
@@ -364,13 +1023,13 @@ pub(crate) mod query {
 
     impl Serialize for SerializeMaybeParent {
         fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-            validate!(@self.parent.map(|x| x.to_bits())).serialize(serializer)
+            super::save_option_ref(self.parent.map(Parent::get))?.serialize(serializer)
         }
     }
 
     impl Serialize for SerializeMaybeParentItem<'_> {
         fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-            validate!(@self.parent.map(|x| x.to_bits())).serialize(serializer)
+            super::save_option_ref(self.parent.map(Parent::get))?.serialize(serializer)
         }
     }
 }
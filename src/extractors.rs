@@ -1,19 +1,29 @@
-use std::{any::type_name, fmt::Debug, marker::PhantomData};
+use std::{
+    any::{type_name, Any, TypeId},
+    cell::RefCell,
+    fmt::Debug,
+    hash::Hash,
+    marker::PhantomData,
+};
 
 use crate::{
-    entity_scope, world_entity_scope, world_entity_scope_mut, BevyObject, BindProject,
-    BindProjectQuery, ZstInit,
+    delta, entity, entity_scope, journal, merge,
+    path::{path_scope, type_scope, PathSegment},
+    value::ValueSerializer,
+    world_entity_scope, world_entity_scope_mut, BevyObject, BindProject, BindProjectQuery, ZstInit,
 };
 use bevy_ecs::{
     entity::Entity,
     query::With,
     system::Resource,
-    world::{FromWorld, World},
+    world::{EntityWorldMut, FromWorld, World},
 };
-use bevy_hierarchy::{BuildWorldChildren, Children, DespawnRecursiveExt};
-use bevy_serde_lens_core::{with_world, with_world_mut};
+use bevy_hierarchy::{BuildWorldChildren, Children, DespawnRecursiveExt, Parent};
+use bevy_reflect::TypePath;
+use bevy_serde_lens_core::{current_entity, with_world, with_world_mut};
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::{
-    de::{DeserializeOwned, SeqAccess, Visitor},
+    de::{DeserializeOwned, DeserializeSeed, MapAccess, SeqAccess, Visitor},
     Deserialize, Deserializer, Serialize, Serializer,
 };
 
@@ -38,8 +48,12 @@ impl<T> ZstInit for Maybe<T> {
     }
 }
 
+/// Here to make `#[serde(default)]` work.
 impl<T: BevyObject> Default for Maybe<T> {
     fn default() -> Self {
+        if let Ok(entity) = current_entity() {
+            merge::record_absent::<T>(entity);
+        }
         Self(PhantomData)
     }
 }
@@ -47,6 +61,10 @@ impl<T: BevyObject> Default for Maybe<T> {
 impl<T: BevyObject> BindProject for Maybe<T> {
     type To = Self;
     type Filter = ();
+
+    fn remove(entity: &mut EntityWorldMut) {
+        T::remove(entity)
+    }
 }
 
 impl<T: BevyObject> BindProjectQuery for Maybe<T> {
@@ -55,7 +73,7 @@ impl<T: BevyObject> BindProjectQuery for Maybe<T> {
 
 impl<T: BevyObject> Serialize for Maybe<T> {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        world_entity_scope::<_, S>(|world, entity| match world.get_entity(entity) {
+        let object = world_entity_scope::<_, S>(|world, entity| match world.get_entity(entity) {
             Some(entity_ref) => {
                 if T::filter(&entity_ref) {
                     Some(T::init())
@@ -64,20 +82,41 @@ impl<T: BevyObject> Serialize for Maybe<T> {
                 }
             }
             None => None,
-        })?
-        .serialize(serializer)
+        })?;
+        // Buffer the object to omit it if it contains dangling references,
+        // only with `DanglingPolicy::DropComponent`.
+        if let Some(object) = &object {
+            if entity::drop_dangling() {
+                let human_readable = serializer.is_human_readable();
+                let (value, dangling) =
+                    entity::dangling_scope(|| object.serialize(ValueSerializer { human_readable }));
+                if dangling {
+                    return None::<T::Object>.serialize(serializer);
+                }
+                let value = value.map_err(serde::ser::Error::custom)?;
+                return Some(value).serialize(serializer);
+            }
+        }
+        object.serialize(serializer)
     }
 }
 
 impl<'de, T: BevyObject> Deserialize<'de> for Maybe<T> {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        <Option<T::Object>>::deserialize(deserializer)?;
+        if <Option<T::Object>>::deserialize(deserializer)?.is_none() {
+            if let Ok(entity) = current_entity() {
+                merge::record_absent::<T>(entity);
+            }
+        }
         Ok(Self(PhantomData))
     }
 }
 
 impl<T: BevyObject> Default for Maybe<Child<T>> {
     fn default() -> Self {
+        if let Ok(entity) = current_entity() {
+            merge::record_children::<T>(entity);
+        }
         Self(PhantomData)
     }
 }
@@ -85,6 +124,11 @@ impl<T: BevyObject> Default for Maybe<Child<T>> {
 impl<T: BevyObject> BindProject for Maybe<Child<T>> {
     type To = Self;
     type Filter = ();
+
+    fn remove(entity: &mut EntityWorldMut) {
+        let id = entity.id();
+        entity.world_scope(|world| merge::despawn_children::<T>(world, id));
+    }
 }
 
 impl<T: BevyObject> Serialize for Maybe<Child<T>> {
@@ -117,7 +161,11 @@ impl<T: BevyObject> Serialize for Maybe<Child<T>> {
 
 impl<'de, T: BevyObject> Deserialize<'de> for Maybe<Child<T>> {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        <Option<Child<T>>>::deserialize(deserializer)?;
+        if <Option<Child<T>>>::deserialize(deserializer)?.is_none() {
+            if let Ok(entity) = current_entity() {
+                merge::record_children::<T>(entity);
+            }
+        }
         Ok(Self(PhantomData))
     }
 }
@@ -151,6 +199,7 @@ impl<T: Component + FromWorld> Default for DefaultInit<T> {
                 return;
             };
             entity.insert(item);
+            merge::record_default::<T>(entity.id());
         });
         Self(PhantomData)
     }
@@ -159,6 +208,10 @@ impl<T: Component + FromWorld> Default for DefaultInit<T> {
 impl<T: Component + FromWorld> BindProject for DefaultInit<T> {
     type To = Self;
     type Filter = With<T>;
+
+    fn remove(entity: &mut EntityWorldMut) {
+        entity.remove::<T>();
+    }
 }
 
 impl<T: Component + FromWorld> BindProjectQuery for DefaultInit<T> {
@@ -200,6 +253,7 @@ impl<'de, T: Component + FromWorld> Deserialize<'de> for DefaultInit<T> {
                 return Err(serde::de::Error::custom("Entity missing."));
             };
             entity.insert(item);
+            merge::record_default::<T>(entity.id());
             Ok(())
         })??;
         Ok(Self(PhantomData))
@@ -226,11 +280,13 @@ impl<'de, T: BevyObject> Deserialize<'de> for Root<T> {
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_seq(Root(PhantomData))
+        path_scope(PathSegment::Section(T::name()), || {
+            deserializer.deserialize_seq(Root(PhantomData))
+        })
     }
 }
 
-fn safe_despawn(world: &mut World, entity: Entity) {
+pub(crate) fn safe_despawn(world: &mut World, entity: Entity) {
     if let Some(entity) = world.get_entity_mut(entity) {
         entity.despawn_recursive();
     }
@@ -247,7 +303,7 @@ impl<'de, T: BevyObject> Visitor<'de> for Root<T> {
     where
         A: SeqAccess<'de>,
     {
-        loop {
+        for index in 0.. {
             let entity = with_world_mut(|world| {
                 let entity = world.spawn_empty().id();
                 if let Some(mut root) = T::get_root(world) {
@@ -256,7 +312,12 @@ impl<'de, T: BevyObject> Visitor<'de> for Root<T> {
                 entity
             })
             .map_err(serde::de::Error::custom)?;
-            match entity_scope(entity, || seq.next_element::<T::Object>()) {
+            if entity::is_deferred() {
+                merge::track(entity);
+            }
+            match path_scope(PathSegment::Index(index), || {
+                entity_scope(entity, || seq.next_element::<T::Object>())
+            }) {
                 Err(err) => {
                     with_world_mut(|world| safe_despawn(world, entity))
                         .map_err(serde::de::Error::custom)?;
@@ -267,7 +328,7 @@ impl<'de, T: BevyObject> Visitor<'de> for Root<T> {
                         .map_err(serde::de::Error::custom)?;
                     break;
                 }
-                Ok(Some(_)) => {}
+                Ok(Some(_)) => journal::record_spawn(T::name(), entity),
             }
         }
         Ok(Root(PhantomData))
@@ -292,6 +353,10 @@ impl<T> ZstInit for SerializeComponent<T> {
 impl<T: Component + Serialize + DeserializeOwned> BindProject for SerializeComponent<T> {
     type To = Self;
     type Filter = With<T>;
+
+    fn remove(entity: &mut EntityWorldMut) {
+        entity.remove::<T>();
+    }
 }
 
 impl<T: Component + Serialize + DeserializeOwned> BindProjectQuery for SerializeComponent<T> {
@@ -312,6 +377,10 @@ impl<T: Component + Serialize> Serialize for SerializeComponent<T> {
                     std::any::type_name::<T>()
                 )));
             };
+            entity::check_map_entities::<T, S::Error>(world)?;
+            if let Some(ticks) = entity.get_change_ticks::<T>() {
+                delta::probe(ticks);
+            }
             component.serialize(serializer)
         })?
     }
@@ -322,7 +391,7 @@ impl<'de, T: Component + Deserialize<'de>> Deserialize<'de> for SerializeCompone
     where
         D: serde::Deserializer<'de>,
     {
-        let component = T::deserialize(deserializer)?;
+        let component = type_scope::<T, _, _>(|| T::deserialize(deserializer))?;
         world_entity_scope_mut::<_, D>(|world, entity| {
             let Some(mut entity) = world.get_entity_mut(entity) else {
                 return Err(serde::de::Error::custom(format!(
@@ -330,6 +399,110 @@ impl<'de, T: Component + Deserialize<'de>> Deserialize<'de> for SerializeCompone
                 )));
             };
             entity.insert(component);
+            let entity = entity.id();
+            merge::record_component::<T>(entity);
+            entity::record_map_entities::<T>(world, entity);
+            Ok(Self(PhantomData))
+        })?
+    }
+}
+
+/// Convert a component `T` to and from a serializable proxy type.
+///
+/// This allows components without serde support to be used in a [`BevyObject`]
+/// with [`Adapted`] or `#[bevy_object(adapter = "Adapter")]`.
+///
+/// # Example
+///
+/// ```
+/// pub struct ColorAdapter;
+///
+/// impl SerializeAs<ForeignColor> for ColorAdapter {
+///     type Proxy = [f32; 4];
+///
+///     fn to_proxy(value: &ForeignColor) -> [f32; 4] {
+///         value.to_array()
+///     }
+///
+///     fn from_proxy(proxy: [f32; 4]) -> ForeignColor {
+///         ForeignColor::from_array(proxy)
+///     }
+/// }
+/// ```
+pub trait SerializeAs<T> {
+    /// The serialized representation of `T`.
+    type Proxy: Serialize + DeserializeOwned;
+
+    /// Convert the component to the proxy type.
+    fn to_proxy(value: &T) -> Self::Proxy;
+
+    /// Convert the proxy type back to the component.
+    fn from_proxy(proxy: Self::Proxy) -> T;
+}
+
+/// Serialize a component on the active entity via a [`SerializeAs`] adapter `A`.
+pub struct Adapted<T, A>(PhantomData<(T, A)>);
+
+impl<T, A> Debug for Adapted<T, A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Adapted").finish()
+    }
+}
+
+impl<T, A> ZstInit for Adapted<T, A> {
+    fn init() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Component, A: SerializeAs<T>> BindProject for Adapted<T, A> {
+    type To = Self;
+    type Filter = With<T>;
+
+    fn remove(entity: &mut EntityWorldMut) {
+        entity.remove::<T>();
+    }
+}
+
+impl<T: Component, A: SerializeAs<T>> Serialize for Adapted<T, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        world_entity_scope::<_, S>(|world, entity| {
+            let Some(entity) = world.get_entity(entity) else {
+                return Err(serde::ser::Error::custom(format!(
+                    "Entity missing: {entity:?}."
+                )));
+            };
+            let Some(component) = entity.get::<T>() else {
+                return Err(serde::ser::Error::custom(format!(
+                    "Component missing: {}.",
+                    std::any::type_name::<T>()
+                )));
+            };
+            entity::check_map_entities::<T, S::Error>(world)?;
+            if let Some(ticks) = entity.get_change_ticks::<T>() {
+                delta::probe(ticks);
+            }
+            A::to_proxy(component).serialize(serializer)
+        })?
+    }
+}
+
+impl<'de, T: Component, A: SerializeAs<T>> Deserialize<'de> for Adapted<T, A> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let proxy = type_scope::<T, _, _>(|| A::Proxy::deserialize(deserializer))?;
+        world_entity_scope_mut::<_, D>(|world, entity| {
+            let Some(mut entity) = world.get_entity_mut(entity) else {
+                return Err(serde::de::Error::custom(format!(
+                    "Entity missing {entity:?}."
+                )));
+            };
+            entity.insert(A::from_proxy(proxy));
+            let entity = entity.id();
+            merge::record_component::<T>(entity);
+            entity::record_map_entities::<T>(world, entity);
             Ok(Self(PhantomData))
         })?
     }
@@ -365,14 +538,26 @@ impl<T: Resource + Serialize> Serialize for SerializeResource<T> {
     }
 }
 
-impl<'de, T: Resource + Deserialize<'de>> Deserialize<'de> for SerializeResource<T> {
+impl<'de, T: Resource + Deserialize<'de> + TypePath> Deserialize<'de> for SerializeResource<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let resource = T::deserialize(deserializer)?;
-        with_world_mut(|world| world.insert_resource(resource))
-            .map_err(serde::de::Error::custom)?;
+        let resource = type_scope::<T, _, _>(|| T::deserialize(deserializer))?;
+        with_world_mut(|world| {
+            if journal::is_active() {
+                let previous = world.remove_resource::<T>();
+                journal::record_undo(move |world| match previous {
+                    Some(previous) => world.insert_resource(previous),
+                    None => {
+                        world.remove_resource::<T>();
+                    }
+                });
+            }
+            journal::record_resource(T::short_type_path());
+            world.insert_resource(resource)
+        })
+        .map_err(serde::de::Error::custom)?;
         Ok(Self(PhantomData))
     }
 }
@@ -407,14 +592,26 @@ impl<T: Serialize + 'static> Serialize for SerializeNonSend<T> {
     }
 }
 
-impl<'de, T: Deserialize<'de> + 'static> Deserialize<'de> for SerializeNonSend<T> {
+impl<'de, T: Deserialize<'de> + TypePath> Deserialize<'de> for SerializeNonSend<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let resource = T::deserialize(deserializer)?;
-        with_world_mut(|world| world.insert_non_send_resource(resource))
-            .map_err(serde::de::Error::custom)?;
+        let resource = type_scope::<T, _, _>(|| T::deserialize(deserializer))?;
+        with_world_mut(|world| {
+            if journal::is_active() {
+                let previous = world.remove_non_send_resource::<T>();
+                journal::record_undo(move |world| match previous {
+                    Some(previous) => world.insert_non_send_resource(previous),
+                    None => {
+                        world.remove_non_send_resource::<T>();
+                    }
+                });
+            }
+            journal::record_resource(T::short_type_path());
+            world.insert_non_send_resource(resource)
+        })
+        .map_err(serde::de::Error::custom)?;
         Ok(Self(PhantomData))
     }
 }
@@ -438,6 +635,11 @@ impl<T> Debug for Child<T> {
 impl<T: BevyObject> BindProject for Child<T> {
     type To = Self;
     type Filter = ();
+
+    fn remove(entity: &mut EntityWorldMut) {
+        let id = entity.id();
+        entity.world_scope(|world| merge::despawn_children::<T>(world, id));
+    }
 }
 
 impl<T: BevyObject> Serialize for Child<T> {
@@ -481,8 +683,12 @@ impl<'de, T: BevyObject> Deserialize<'de> for Child<T> {
         let new_child = world_entity_scope_mut::<_, D>(|world, entity| {
             let child = world.spawn_empty().id();
             world.entity_mut(entity).add_child(child);
+            merge::record_children::<T>(entity);
             child
         })?;
+        if entity::is_deferred() {
+            merge::track(new_child);
+        }
         entity_scope(new_child, || <T::Object>::deserialize(deserializer))
             .map_err(serde::de::Error::custom)?;
         Ok(Child(PhantomData))
@@ -566,7 +772,14 @@ impl<'de, T: BevyObject> Visitor<'de> for ChildVec<T> {
     where
         A: SeqAccess<'de>,
     {
-        while seq.next_element::<Child<T>>()?.is_some() {}
+        if let Ok(entity) = current_entity() {
+            merge::record_children::<T>(entity);
+        }
+        for index in 0.. {
+            if path_scope(PathSegment::Index(index), || seq.next_element::<Child<T>>())?.is_none() {
+                break;
+            }
+        }
         Ok(ChildVec(PhantomData))
     }
 }
@@ -574,4 +787,550 @@ impl<'de, T: BevyObject> Visitor<'de> for ChildVec<T> {
 impl<T: BevyObject> BindProject for ChildVec<T> {
     type To = Self;
     type Filter = ();
+
+    fn remove(entity: &mut EntityWorldMut) {
+        let id = entity.id();
+        entity.world_scope(|world| merge::despawn_children::<T>(world, id));
+    }
+}
+
+/// Extractor for [`BevyObject`]s in descendants of arbitrary depth
+/// instead of the entity itself.
+///
+/// Children matching `T`, their children matching `T` and so on are serialized
+/// as nested sequences of `(T, children)` and the same hierarchy is rebuilt on load.
+/// Descendants of children not matching `T` are not serialized.
+pub struct ChildTree<T>(PhantomData<T>);
+
+impl<T> Debug for ChildTree<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChildTree").finish()
+    }
+}
+
+impl<T> ZstInit for ChildTree<T> {
+    fn init() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// This is allowed since `0` is a valid number of children.
+impl<T> Default for ChildTree<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: BevyObject> Serialize for ChildTree<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeSeq;
+        world_entity_scope::<_, S>(|world, entity| {
+            let Some(entity) = world.get_entity(entity) else {
+                return Err(serde::ser::Error::custom(format!(
+                    "Entity missing {entity:?}."
+                )));
+            };
+            let children = match entity.get::<Children>() {
+                Some(children) => children.as_ref(),
+                None => &[],
+            };
+            let count = children
+                .iter()
+                .filter_map(|e| world.get_entity(*e))
+                .filter(T::filter)
+                .count();
+            let mut seq = serializer.serialize_seq(Some(count))?;
+            for entity in children
+                .iter()
+                .filter_map(|e| world.get_entity(*e))
+                .filter(T::filter)
+            {
+                entity_scope(entity.id(), || {
+                    seq.serialize_element(&(T::init(), ChildTree::<T>::init()))
+                })?;
+            }
+            seq.end()
+        })?
+    }
+}
+
+impl<'de, T: BevyObject> Deserialize<'de> for ChildTree<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(ChildTree(PhantomData))
+    }
+}
+
+impl<'de, T: BevyObject> Visitor<'de> for ChildTree<T> {
+    type Value = ChildTree<T>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a sequence of entities and their children")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        if let Ok(entity) = current_entity() {
+            merge::record_children::<T>(entity);
+        }
+        for index in 0.. {
+            if path_scope(PathSegment::Index(index), || {
+                seq.next_element::<TreeNode<T>>()
+            })?
+            .is_none()
+            {
+                break;
+            }
+        }
+        Ok(ChildTree(PhantomData))
+    }
+}
+
+impl<T: BevyObject> BindProject for ChildTree<T> {
+    type To = Self;
+    type Filter = ();
+
+    fn remove(entity: &mut EntityWorldMut) {
+        let id = entity.id();
+        entity.world_scope(|world| merge::despawn_children::<T>(world, id));
+    }
+}
+
+/// Deserialize a [`BevyObject`] and its descendants as a child in a [`ChildTree`].
+struct TreeNode<T>(PhantomData<T>);
+
+impl<'de, T: BevyObject> Deserialize<'de> for TreeNode<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let new_child = world_entity_scope_mut::<_, D>(|world, entity| {
+            let child = world.spawn_empty().id();
+            world.entity_mut(entity).add_child(child);
+            child
+        })?;
+        if entity::is_deferred() {
+            merge::track(new_child);
+        }
+        entity_scope(new_child, || {
+            <(T::Object, ChildTree<T>)>::deserialize(deserializer)
+        })?;
+        Ok(TreeNode(PhantomData))
+    }
+}
+
+/// Extractor for multiple [`BevyObject`]s in [`Children`]
+/// instead of the entity itself. This serializes children like a map keyed by component `K`.
+///
+/// `K` is inserted on each child during deserialization.
+///
+/// # Errors
+///
+/// If a matching child has no `K` or if keys are duplicated.
+pub struct ChildMap<K, T>(PhantomData<(K, T)>);
+
+impl<K, T> Debug for ChildMap<K, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChildMap").finish()
+    }
+}
+
+impl<K, T> ZstInit for ChildMap<K, T> {
+    fn init() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// This is allowed since `0` is a valid number of children.
+impl<K, T> Default for ChildMap<K, T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<K, T> Serialize for ChildMap<K, T>
+where
+    K: Component + Serialize + Eq + Hash,
+    T: BevyObject,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeMap;
+        world_entity_scope::<_, S>(|world, entity| {
+            let Some(entity) = world.get_entity(entity) else {
+                return Err(serde::ser::Error::custom(format!(
+                    "Entity missing {entity:?}."
+                )));
+            };
+            let children = match entity.get::<Children>() {
+                Some(children) => children.as_ref(),
+                None => &[],
+            };
+            let children: Vec<_> = children
+                .iter()
+                .filter_map(|e| world.get_entity(*e))
+                .filter(T::filter)
+                .collect();
+            let mut keys = FxHashSet::default();
+            let mut map = serializer.serialize_map(Some(children.len()))?;
+            for entity in children {
+                let Some(key) = entity.get::<K>() else {
+                    return Err(serde::ser::Error::custom(format!(
+                        "Key missing: {}.",
+                        type_name::<K>()
+                    )));
+                };
+                if !keys.insert(key) {
+                    return Err(serde::ser::Error::custom(format!(
+                        "Duplicate key: {}.",
+                        type_name::<K>()
+                    )));
+                }
+                map.serialize_key(key)?;
+                entity_scope(entity.id(), || map.serialize_value(&T::init()))?;
+            }
+            map.end()
+        })?
+    }
+}
+
+impl<'de, K, T> Deserialize<'de> for ChildMap<K, T>
+where
+    K: Component + DeserializeOwned + Eq + Hash + Clone,
+    T: BevyObject,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(ChildMap(PhantomData))
+    }
+}
+
+impl<'de, K, T> Visitor<'de> for ChildMap<K, T>
+where
+    K: Component + DeserializeOwned + Eq + Hash + Clone,
+    T: BevyObject,
+{
+    type Value = ChildMap<K, T>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map of entities")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        if let Ok(entity) = current_entity() {
+            merge::record_children::<T>(entity);
+        }
+        let mut keys = FxHashSet::default();
+        for index in 0.. {
+            let finished = path_scope(PathSegment::Index(index), || {
+                let Some(key) = map.next_key::<K>()? else {
+                    return Ok(true);
+                };
+                if !keys.insert(key.clone()) {
+                    return Err(serde::de::Error::custom(format!(
+                        "Duplicate key: {}.",
+                        type_name::<K>()
+                    )));
+                }
+                map.next_value_seed(KeyedChild::<K, T>(key, PhantomData))?;
+                Ok(false)
+            })?;
+            if finished {
+                break;
+            }
+        }
+        Ok(ChildMap(PhantomData))
+    }
+}
+
+impl<K, T> BindProject for ChildMap<K, T>
+where
+    K: Component + Serialize + DeserializeOwned + Eq + Hash + Clone,
+    T: BevyObject,
+{
+    type To = Self;
+    type Filter = ();
+
+    fn remove(entity: &mut EntityWorldMut) {
+        let id = entity.id();
+        entity.world_scope(|world| merge::despawn_children::<T>(world, id));
+    }
+}
+
+/// Deserialize a [`BevyObject`] as a child with key component `K`.
+struct KeyedChild<K, T>(K, PhantomData<T>);
+
+impl<'de, K: Component, T: BevyObject> DeserializeSeed<'de> for KeyedChild<K, T> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let new_child = world_entity_scope_mut::<_, D>(|world, entity| {
+            let child = world.spawn(self.0).id();
+            world.entity_mut(entity).add_child(child);
+            child
+        })?;
+        if entity::is_deferred() {
+            merge::track(new_child);
+            merge::record_component::<K>(new_child);
+        }
+        entity_scope(new_child, || <T::Object>::deserialize(deserializer))
+            .map_err(serde::de::Error::custom)?;
+        Ok(())
+    }
+}
+
+/// Extractor for a [`BevyObject`] on the [`Parent`]
+/// instead of the entity itself, matched by key component `K`.
+///
+/// On load, the parent is attached to an existing entity satisfying `T`'s filter
+/// with an equal `K`, otherwise a new parent is spawned.
+/// A matched parent is not modified, [`EntityId`](crate::EntityId)s in its data point to it.
+/// This means siblings loaded from the same save share the same parent,
+/// and `K` should be unique among parents.
+/// Parents without `K` are never shared.
+///
+/// Parents are matched by `K` rather than by comparing serialized data,
+/// which would be slow and would merge different parents that happen to have equal data.
+///
+/// Spawned parents are despawned by `despawn_bound_objects` and `load_replace`
+/// once they have no children left.
+pub struct ParentObject<T, K>(PhantomData<(T, K)>);
+
+impl<T, K> ZstInit for ParentObject<T, K> {
+    fn init() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T, K> Debug for ParentObject<T, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParentObject").finish()
+    }
+}
+
+impl<T: BevyObject, K: Component + Eq + Hash + Clone> BindProject for ParentObject<T, K> {
+    type To = Self;
+    type Filter = With<Parent>;
+
+    fn remove(entity: &mut EntityWorldMut) {
+        entity.remove_parent();
+    }
+}
+
+impl<T: BevyObject, K: Component + Eq + Hash + Clone> Serialize for ParentObject<T, K> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match find_parent::<T, S>()? {
+            Some(parent) => entity_scope(parent, || T::init().serialize(serializer)),
+            None => Err(serde::ser::Error::custom(format!(
+                "No valid parent found for {}.",
+                type_name::<T>()
+            ))),
+        }
+    }
+}
+
+impl<'de, T: BevyObject, K: Component + Eq + Hash + Clone> Deserialize<'de> for ParentObject<T, K> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        load_parent::<T, K, D>(deserializer)?;
+        Ok(ParentObject(PhantomData))
+    }
+}
+
+impl<T: BevyObject, K: Component + Eq + Hash + Clone> Default for Maybe<ParentObject<T, K>> {
+    fn default() -> Self {
+        if let Ok(entity) = current_entity() {
+            merge::record_orphan(entity);
+        }
+        Self(PhantomData)
+    }
+}
+
+impl<T: BevyObject, K: Component + Eq + Hash + Clone> BindProject for Maybe<ParentObject<T, K>> {
+    type To = Self;
+    type Filter = ();
+
+    fn remove(entity: &mut EntityWorldMut) {
+        entity.remove_parent();
+    }
+}
+
+impl<T: BevyObject, K: Component + Eq + Hash + Clone> Serialize for Maybe<ParentObject<T, K>> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match find_parent::<T, S>()? {
+            Some(parent) => entity_scope(parent, || Some(T::init()).serialize(serializer)),
+            None => None::<T::Object>.serialize(serializer),
+        }
+    }
+}
+
+impl<'de, T: BevyObject, K: Component + Eq + Hash + Clone> Deserialize<'de>
+    for Maybe<ParentObject<T, K>>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if <Option<ParentObject<T, K>>>::deserialize(deserializer)?.is_none() {
+            if let Ok(entity) = current_entity() {
+                merge::record_orphan(entity);
+            }
+        }
+        Ok(Self(PhantomData))
+    }
+}
+
+/// Marker for a parent spawned by [`ParentObject`].
+#[derive(Component)]
+struct SpawnedParent;
+
+/// Despawn parents spawned by [`ParentObject`] that have no children left.
+pub(crate) fn despawn_empty_parents(world: &mut World) {
+    let mut query = world.query_filtered::<(Entity, Option<&Children>), With<SpawnedParent>>();
+    let empty: Vec<_> = query
+        .iter(world)
+        .filter(|(_, children)| children.is_none_or(|x| x.is_empty()))
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in empty {
+        safe_despawn(world, entity);
+    }
+}
+
+/// Find the parent of the current entity if it satisfies [`BevyObject`] `T`.
+fn find_parent<T: BevyObject, S: Serializer>() -> Result<Option<Entity>, S::Error> {
+    world_entity_scope::<_, S>(|world, entity| {
+        let Some(entity) = world.get_entity(entity) else {
+            return Err(serde::ser::Error::custom(format!(
+                "Entity missing {entity:?}."
+            )));
+        };
+        Ok(entity
+            .get::<Parent>()
+            .and_then(|parent| world.get_entity(parent.get()))
+            .filter(T::filter)
+            .map(|parent| parent.id()))
+    })?
+}
+
+/// Deserialize a [`BevyObject`] as the parent of the current entity,
+/// reusing an existing entity with an equal key component `K`.
+fn load_parent<'de, T: BevyObject, K: Component + Eq + Hash + Clone, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<(), D::Error> {
+    let parent = world_entity_scope_mut::<_, D>(|world, _| world.spawn(SpawnedParent).id())?;
+    // Tracked so data shared with a matching parent, i.e. its `PersistentId`, is accepted.
+    merge::track(parent);
+    let result = entity_scope(parent, || <T::Object>::deserialize(deserializer));
+    if let Err(err) = result {
+        merge::untrack(parent);
+        with_world_mut(|world| safe_despawn(world, parent)).map_err(serde::de::Error::custom)?;
+        return Err(err);
+    }
+    world_entity_scope_mut::<_, D>(|world, entity| {
+        let existing = find_key::<T, K>(world, parent);
+        let duplicates = merge::check_duplicates(parent, existing);
+        merge::untrack(parent);
+        if let Err(err) = duplicates {
+            safe_despawn(world, parent);
+            return Err(serde::de::Error::custom(err));
+        }
+        let parent = match existing {
+            Some(existing) => {
+                world.entity_mut(parent).despawn_recursive();
+                if entity::is_pending(parent) {
+                    return Err(serde::de::Error::custom(format!(
+                        "{} is referenced before it is deserialized, \
+                        which is not supported when it matches an existing parent.",
+                        T::name()
+                    )));
+                }
+                entity::remap(parent, existing);
+                existing
+            }
+            None => {
+                journal::record_parent(parent);
+                parent
+            }
+        };
+        if let Some(mut entity) = world.get_entity_mut(entity) {
+            entity.set_parent(parent);
+        }
+        Ok(())
+    })?
+}
+
+/// Map of keys to parents of a [`ParentObject`], by [`BevyObject`] name and key type.
+pub(crate) type ParentKeys = FxHashMap<(&'static str, TypeId), Box<dyn Any>>;
+
+thread_local! {
+    /// Parents of each [`ParentObject`] type by key, collected on first use in a `load`.
+    static PARENT_KEYS: RefCell<ParentKeys> = RefCell::new(FxHashMap::default());
+}
+
+/// Clear parents collected by [`ParentObject`]s before a `load`.
+pub(crate) fn clear_parent_keys() {
+    PARENT_KEYS.with(|m| m.borrow_mut().clear());
+}
+
+/// Swap parents collected by [`ParentObject`]s with those of a paused `load`.
+pub(crate) fn swap_parent_keys(keys: &mut ParentKeys) {
+    PARENT_KEYS.with(|m| std::mem::swap(&mut *m.borrow_mut(), keys));
+}
+
+/// Find another entity satisfying [`BevyObject`] `T` with the same `K` as `entity`,
+/// otherwise record `entity` as the parent of its key.
+fn find_key<T: BevyObject, K: Component + Eq + Hash + Clone>(
+    world: &mut World,
+    entity: Entity,
+) -> Option<Entity> {
+    let key = world
+        .get_entity(entity)
+        .filter(T::filter)?
+        .get::<K>()?
+        .clone();
+    PARENT_KEYS.with(|m| {
+        let mut m = m.borrow_mut();
+        let keys = m
+            .entry((T::name(), TypeId::of::<K>()))
+            .or_insert_with(|| {
+                let mut query = world.query_filtered::<(Entity, &K), T::Filter>();
+                let keys: FxHashMap<K, Entity> = query
+                    .iter(world)
+                    .filter(|(other, _)| *other != entity)
+                    .map(|(other, key)| (key.clone(), other))
+                    .collect();
+                Box::new(keys)
+            })
+            .downcast_mut::<FxHashMap<K, Entity>>()?;
+        // Parents may have changed between steps of an incremental load.
+        let existing = keys.get(&key).copied().filter(|existing| {
+            world
+                .get_entity(*existing)
+                .is_some_and(|e| T::filter(&e) && e.get::<K>() == Some(&key))
+        });
+        if existing.is_none() {
+            keys.insert(key, entity);
+        }
+        existing
+    })
 }
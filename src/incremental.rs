@@ -0,0 +1,219 @@
+//! Module for loading large saves across multiple frames.
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use bevy_ecs::world::World;
+use serde::{
+    de::{IgnoredAny, MapAccess},
+    Deserialize, Deserializer,
+};
+
+use crate::{
+    entity::IdMaps,
+    save_load::typetag_scope,
+    value::{self, MapDeserializer, Value},
+    with_world_mut, BatchSerialization, SectionPolicy,
+};
+
+/// Amount of work done in a single [`IncrementalLoad::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadBudget {
+    /// Spawn at most this many entities, including children, must be greater than 0.
+    ///
+    /// An object is never split across steps,
+    /// so a step may exceed the budget by the entities of one object.
+    Entities(usize),
+    /// Stop after this duration has elapsed.
+    ///
+    /// At least one entity or resource is loaded per step.
+    Time(Duration),
+}
+
+/// A section waiting to be loaded.
+struct Section {
+    name: String,
+    /// If set, items of an entity sequence, otherwise the entire section.
+    items: Option<VecDeque<Value>>,
+    value: Value,
+}
+
+/// A resumable loader of a [`BatchSerialization`] type that spawns entities across frames.
+///
+/// The file is parsed into a [`Value`] on creation, which requires a self-describing format
+/// like `json`. Each call to `step` then spawns entities within a [`LoadBudget`].
+///
+/// Unlike `load`, entities spawned by previous steps are kept on error.
+/// After an error, the loader is failed and `step` returns an error.
+///
+/// # Example
+/// ```
+/// let mut loader = IncrementalLoad::<SaveFile>::new(deserializer)?;
+/// // In a system, each frame.
+/// if !loader.step(world, LoadBudget::Entities(100))? {
+///     show_progress(loader.progress());
+/// }
+/// ```
+pub struct IncrementalLoad<T: BatchSerialization> {
+    sections: VecDeque<Section>,
+    ids: IdMaps,
+    human_readable: bool,
+    total: usize,
+    loaded: usize,
+    error: Option<String>,
+    p: PhantomData<fn() -> T>,
+}
+
+impl<T: BatchSerialization> IncrementalLoad<T> {
+    /// Parse a [`BatchSerialization`] type without loading it.
+    pub fn new<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let human_readable = deserializer.is_human_readable();
+        let value = Value::deserialize(deserializer)?;
+        let entries = if T::LEN == 1 {
+            vec![(T::names()[0].to_owned(), value)]
+        } else {
+            let entries = match value {
+                Value::Map(entries) => entries,
+                // `Positional` batches.
+                Value::Seq(items) | Value::Tuple(items) if items.len() == T::names().len() => {
+                    T::names()
+                        .into_iter()
+                        .map(|name| Value::String(name.to_owned()))
+                        .zip(items)
+                        .collect()
+                }
+                _ => return Err(serde::de::Error::custom("expected a map of types.")),
+            };
+            entries
+                .into_iter()
+                .map(|(key, value)| match key {
+                    Value::String(key) => Ok((key, value)),
+                    _ => Err(serde::de::Error::custom("expected a string as type name.")),
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        let mut total = 0;
+        let sections = entries
+            .into_iter()
+            .map(|(name, value)| match value {
+                Value::Seq(items) if T::is_entities(&name) => {
+                    total += items.len();
+                    Section {
+                        name,
+                        items: Some(items.into()),
+                        value: Value::Unit,
+                    }
+                }
+                value => {
+                    total += 1;
+                    Section {
+                        name,
+                        items: None,
+                        value,
+                    }
+                }
+            })
+            .collect();
+        Ok(IncrementalLoad {
+            sections,
+            ids: IdMaps::default(),
+            human_readable,
+            total,
+            loaded: 0,
+            error: None,
+            p: PhantomData,
+        })
+    }
+
+    /// Load entities and resources within a [`LoadBudget`],
+    /// returns true if loading is finished.
+    ///
+    /// # Errors
+    ///
+    /// If loading fails, or has failed in a previous step.
+    ///
+    /// # Panics
+    ///
+    /// If budget is `LoadBudget::Entities(0)`.
+    pub fn step(&mut self, world: &mut World, budget: LoadBudget) -> Result<bool, value::Error> {
+        if let LoadBudget::Entities(count) = budget {
+            assert_ne!(count, 0, "LoadBudget::Entities must be greater than 0.");
+        }
+        if let Some(error) = &self.error {
+            return Err(serde::de::Error::custom(format!(
+                "Loading has failed: {error}"
+            )));
+        }
+        let start = Instant::now();
+        let mut spent = 0;
+        self.ids.swap();
+        let result: Result<(), value::Error> = typetag_scope(world, || {
+            while let Some(section) = self.sections.front_mut() {
+                match budget {
+                    LoadBudget::Entities(count) if spent >= count => break,
+                    LoadBudget::Time(time) if spent > 0 && start.elapsed() >= time => break,
+                    _ => (),
+                };
+                let value = match &mut section.items {
+                    Some(items) => Value::Seq(items.pop_front().into_iter().collect()),
+                    None => std::mem::replace(&mut section.value, Value::Unit),
+                };
+                let name = section.name.clone();
+                if section.items.as_ref().is_none_or(|x| x.is_empty()) {
+                    self.sections.pop_front();
+                }
+                self.loaded += 1;
+                if T::LEN != 1 && !T::names().contains(&name.as_str()) {
+                    spent += 1;
+                    SectionPolicy::unknown::<value::Error>(&name)?;
+                    continue;
+                }
+                let len = with_world_mut(|world| world.entities().len()).unwrap_or(0);
+                load_section::<T>(name, value, self.human_readable)?;
+                let spawned = with_world_mut(|world| world.entities().len()).unwrap_or(0);
+                // Resources count as 1 so each step makes progress.
+                spent += spawned.saturating_sub(len).max(1) as usize;
+            }
+            Ok(())
+        });
+        self.ids.swap();
+        if let Err(error) = &result {
+            self.error = Some(error.to_string());
+        }
+        result.map(|_| self.is_finished())
+    }
+
+    /// Returns true if loading is finished.
+    ///
+    /// Returns false if loading has failed.
+    pub fn is_finished(&self) -> bool {
+        self.error.is_none() && self.sections.is_empty()
+    }
+
+    /// Returns true if a previous `step` has failed.
+    pub fn is_failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Returns the progress of loading, from `0.0` to `1.0`.
+    pub fn progress(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.loaded as f32 / self.total as f32
+        }
+    }
+}
+
+/// Load a section or a part of an entity section.
+fn load_section<T: BatchSerialization>(
+    name: String,
+    value: Value,
+    human_readable: bool,
+) -> Result<(), value::Error> {
+    let mut map = MapDeserializer::new(vec![(Value::String(name.clone()), value)], human_readable);
+    map.next_key::<IgnoredAny>()?;
+    T::deserialize_map(&name, &mut map)
+}
@@ -0,0 +1,126 @@
+//! Module for reverting changes made by a failed `load`.
+use std::cell::RefCell;
+
+use bevy_ecs::{entity::Entity, world::World};
+
+use crate::safe_despawn;
+
+type UndoFn = Box<dyn FnOnce(&mut World)>;
+
+/// Changes made to the [`World`] during a `load`.
+#[derive(Default)]
+pub(crate) struct Journal {
+    /// Spawned root entities and their section names.
+    pub(crate) spawned: Vec<(&'static str, Entity)>,
+    /// Spawned parents of [`ParentObject`](crate::ParentObject)s.
+    pub(crate) parents: Vec<Entity>,
+    /// Names of inserted resources.
+    pub(crate) resources: Vec<&'static str>,
+    /// Names of sections present in a batch.
+    pub(crate) sections: Vec<&'static str>,
+    /// Names of unknown sections skipped.
+    pub(crate) unknown_sections: Vec<String>,
+    undo: Vec<UndoFn>,
+}
+
+thread_local! {
+    static JOURNAL: RefCell<Option<Journal>> = const { RefCell::new(None) };
+}
+
+/// Returns true if changes are being recorded.
+pub(crate) fn is_active() -> bool {
+    JOURNAL.with(|j| j.borrow().is_some())
+}
+
+/// Record a spawned root entity, which will be despawned recursively on rollback.
+pub(crate) fn record_spawn(section: &'static str, entity: Entity) {
+    JOURNAL.with(|j| {
+        if let Some(journal) = j.borrow_mut().as_mut() {
+            journal.spawned.push((section, entity));
+        }
+    })
+}
+
+/// Record a spawned parent, which will be despawned recursively on rollback.
+pub(crate) fn record_parent(entity: Entity) {
+    JOURNAL.with(|j| {
+        if let Some(journal) = j.borrow_mut().as_mut() {
+            journal.parents.push(entity);
+        }
+    })
+}
+
+/// Record an inserted resource.
+pub(crate) fn record_resource(name: &'static str) {
+    JOURNAL.with(|j| {
+        if let Some(journal) = j.borrow_mut().as_mut() {
+            journal.resources.push(name);
+        }
+    })
+}
+
+/// Record a section present in a batch.
+pub(crate) fn record_section(name: &'static str) {
+    JOURNAL.with(|j| {
+        if let Some(journal) = j.borrow_mut().as_mut() {
+            journal.sections.push(name);
+        }
+    })
+}
+
+/// Record a skipped unknown section.
+pub(crate) fn record_unknown_section(name: &str) {
+    JOURNAL.with(|j| {
+        if let Some(journal) = j.borrow_mut().as_mut() {
+            journal.unknown_sections.push(name.to_owned());
+        }
+    })
+}
+
+/// Record a spawned root entity being merged into another entity.
+pub(crate) fn record_replace(entity: Entity, target: Entity) {
+    JOURNAL.with(|j| {
+        if let Some(journal) = j.borrow_mut().as_mut() {
+            for (_, item) in journal.spawned.iter_mut() {
+                if *item == entity {
+                    *item = target;
+                }
+            }
+        }
+    })
+}
+
+/// Record a function that reverts a change on rollback.
+pub(crate) fn record_undo(f: impl FnOnce(&mut World) + 'static) {
+    JOURNAL.with(|j| {
+        if let Some(journal) = j.borrow_mut().as_mut() {
+            journal.undo.push(Box::new(f));
+        }
+    })
+}
+
+/// Run a function, if it fails, revert all recorded changes in reverse order,
+/// otherwise returns the recorded changes.
+pub(crate) fn transaction<T, E>(
+    world: &mut World,
+    f: impl FnOnce(&mut World) -> Result<T, E>,
+) -> Result<(T, Journal), E> {
+    let previous = JOURNAL.with(|j| j.replace(Some(Journal::default())));
+    let result = f(world);
+    let journal = JOURNAL.with(|j| j.replace(previous)).unwrap_or_default();
+    match result {
+        Ok(result) => Ok((result, journal)),
+        Err(err) => {
+            for f in journal.undo.into_iter().rev() {
+                f(world);
+            }
+            for (_, entity) in journal.spawned.into_iter().rev() {
+                safe_despawn(world, entity);
+            }
+            for entity in journal.parents.into_iter().rev() {
+                safe_despawn(world, entity);
+            }
+            Err(err)
+        }
+    }
+}
@@ -8,15 +8,33 @@ use serde::{Deserializer, Serializer};
 mod extractors;
 pub use extractors::*;
 mod batch;
+mod delta;
+mod dynamic;
+mod incremental;
+mod journal;
+mod merge;
 mod save_load;
-pub use batch::{BatchSerialization, Join, SerializeWorld};
-pub use save_load::{DeserializeLens, InWorld, SerializeLens, WorldExtension};
+mod versioning;
+pub use batch::{BatchSerialization, Join, Positional, SectionPolicy, SerializeWorld};
+pub use delta::record_removals;
+pub use dynamic::DynamicBatch;
+pub use incremental::{IncrementalLoad, LoadBudget};
+pub use save_load::{DeserializeLens, InWorld, LoadReport, SerializeLens, WorldExtension};
 pub mod asset;
 pub mod entity;
 mod filter;
 pub mod interning;
+pub mod path;
+pub mod persistent;
+#[cfg(feature = "plugin")]
+pub mod plugin;
 pub mod typetagged;
-pub use entity::{EntityId, Parented};
+pub mod value;
+pub use entity::{
+    DanglingPolicy, EntityId, EntityNumbering, EntityRefMode, EntityResolver, Parented,
+};
+pub use path::{LoadError, ObjectPath};
+pub use persistent::{PersistentId, PersistentIds};
 
 pub use filter::EntityFilter;
 
@@ -30,7 +48,7 @@ use bevy_hierarchy::Children;
 #[doc(hidden)]
 pub use bevy_ecs::{
     entity::Entity,
-    query::With,
+    query::{Or, With},
     world::{EntityWorldMut, World},
 };
 #[doc(hidden)]
@@ -58,6 +76,24 @@ fn world_entity_scope_mut<'de, T, S: Deserializer<'de>>(
     with_world_mut(|w| f(w, entity)).map_err(serde::de::Error::custom)
 }
 
+/// Find the first matching variant of an enum [`BevyObject`], used in the derive macro.
+#[doc(hidden)]
+pub fn match_variant<S: Serializer>(filters: &[fn(&EntityRef) -> bool]) -> Result<usize, S::Error> {
+    world_entity_scope::<_, S>(|world, entity| {
+        let Some(entity_ref) = world.get_entity(entity) else {
+            return Err(serde::ser::Error::custom(format!(
+                "Entity missing {entity:?}."
+            )));
+        };
+        filters
+            .iter()
+            .position(|filter| filter(&entity_ref))
+            .ok_or_else(|| {
+                serde::ser::Error::custom(format!("Entity {entity:?} matches no variant."))
+            })
+    })?
+}
+
 /// Equivalent to [`Default`], indicates the type should be a marker ZST, not a concrete type.
 ///
 /// Due to the role of [`Default`] in `#[serde(default)]` and `#[serde(skip)]`,
@@ -96,7 +132,7 @@ pub trait BevyObject {
     /// Obtain the root node to parent this component to if directly called.
     /// Default is `None`, which means no parent.
     #[allow(unused)]
-    fn get_root(world: &mut World) -> Option<EntityWorldMut> {
+    fn get_root(world: &mut World) -> Option<EntityWorldMut<'_>> {
         None
     }
 
@@ -113,6 +149,12 @@ pub trait BevyObject {
         Self::Filter::filter(entity)
     }
 
+    /// Remove the object from an entity, used by `load_merge` on missing [`Maybe`] fields.
+    ///
+    /// Default implementation does nothing.
+    #[allow(unused_variables)]
+    fn remove(entity: &mut EntityWorldMut) {}
+
     /// Convert `Data` to a serializable.
     #[allow(unused_variables)]
     fn into_ser(query_data: Item<'_, Self>) -> impl Serialize {}
@@ -132,6 +174,10 @@ where
         T::short_type_path()
     }
 
+    fn remove(entity: &mut EntityWorldMut) {
+        entity.remove::<T>();
+    }
+
     fn into_ser(query_data: Item<'_, Self>) -> impl Serialize {
         query_data
     }
@@ -141,6 +187,13 @@ where
 pub trait BindProject {
     type To: Serialize + DeserializeOwned + ZstInit;
     type Filter: QueryFilter;
+
+    /// Remove the projected data from an entity, used by [`BevyObject::remove`]
+    /// in the [`BevyObject`] macro.
+    ///
+    /// Default implementation does nothing.
+    #[allow(unused_variables)]
+    fn remove(entity: &mut EntityWorldMut) {}
 }
 
 /// Make a type usable in in the [`BevyObject`] macro in `query` mode.
@@ -155,6 +208,10 @@ where
     type To = T::Object;
     /// Optionally used in the macro if `Filter` is not specified.
     type Filter = T::Filter;
+
+    fn remove(entity: &mut EntityWorldMut) {
+        T::remove(entity)
+    }
 }
 
 impl<T> BindProjectQuery for T
@@ -93,6 +93,10 @@ macro_rules! bind_object {
                     use $crate::TypePath;
                     Self::short_type_path()
                 }
+
+                fn remove(entity: &mut $crate::EntityWorldMut) {
+                    $(<$ty as $crate::BindProject>::remove(entity);)*
+                }
             }
 
             impl $crate::ZstInit for $main {
@@ -164,6 +168,10 @@ macro_rules! bind_query {
                     Self::short_type_path()
                 }
 
+                fn remove(entity: &mut $crate::EntityWorldMut) {
+                    $(<$ty as $crate::BindProject>::remove(entity);)*
+                }
+
                 fn into_ser(query_data: $crate::Item<'_, Self>) -> impl $crate::serde::Serialize{
                     #[derive($crate::serde::Serialize)]
                     $(#[$($head_attr)*])*
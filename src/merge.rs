@@ -0,0 +1,367 @@
+//! Module for merging deserialized objects into existing entities.
+use std::{
+    any::{type_name, TypeId},
+    cell::RefCell,
+    hash::Hash,
+    marker::PhantomData,
+    rc::Rc,
+};
+
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    reflect::{AppTypeRegistry, ReflectComponent},
+    world::World,
+};
+use bevy_hierarchy::{BuildWorldChildren, Children, Parent};
+use bevy_serde_lens_core::with_world_mut;
+use rustc_hash::FxHashMap;
+use serde::{
+    de::{SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use crate::{
+    entity, entity_scope,
+    path::{path_scope, PathSegment},
+    persistent, safe_despawn, BevyObject,
+};
+
+type MergeFn = Box<dyn FnOnce(&mut World, Entity, Entity)>;
+type TargetFn = Rc<dyn Fn(&World, Entity) -> Option<Entity>>;
+
+/// State of an entity being deserialized before merging.
+#[derive(Default)]
+struct Tracked {
+    ops: Vec<MergeFn>,
+    /// If set, references to the entity were resolved before its merge target was known.
+    referenced: bool,
+    /// [`PersistentId`](crate::PersistentId)s registered to other entities in the world.
+    duplicates: Vec<(u64, Entity)>,
+}
+
+thread_local! {
+    static MERGE_OPS: RefCell<FxHashMap<Entity, Tracked>> = RefCell::new(FxHashMap::default());
+    /// Finds the merge target of a tracked entity in `load_merge`.
+    static MERGE_TARGET: RefCell<Option<TargetFn>> = const { RefCell::new(None) };
+}
+
+/// Record an operation that moves data from a tracked entity to its merge target.
+///
+/// Does nothing if the entity is not being tracked.
+pub(crate) fn record(entity: Entity, f: impl FnOnce(&mut World, Entity, Entity) + 'static) {
+    MERGE_OPS.with(|ops| {
+        if let Some(tracked) = ops.borrow_mut().get_mut(&entity) {
+            tracked.ops.push(Box::new(f))
+        }
+    })
+}
+
+/// Obtain the entity a reference to `entity` should point to,
+/// which is its merge target if `entity` is tracked and its target is known.
+pub(crate) fn redirect(entity: Entity) -> Entity {
+    if !is_tracked(entity) {
+        return entity;
+    }
+    let target = MERGE_TARGET
+        .with(|f| f.borrow().clone())
+        .and_then(|f| with_world_mut(|world| f(world, entity)).ok().flatten());
+    match target {
+        Some(target) => target,
+        None => {
+            MERGE_OPS.with(|ops| {
+                if let Some(tracked) = ops.borrow_mut().get_mut(&entity) {
+                    tracked.referenced = true;
+                }
+            });
+            entity
+        }
+    }
+}
+
+/// Move component `T` to the merge target.
+pub(crate) fn record_component<T: Component>(entity: Entity) {
+    record(entity, |world, from, to| {
+        let Some(component) = world.get_entity_mut(from).and_then(|mut e| e.take::<T>()) else {
+            return;
+        };
+        if let Some(mut entity) = world.get_entity_mut(to) {
+            entity.insert(component);
+        }
+    })
+}
+
+/// Move component `T` to the merge target if the target does not have one.
+pub(crate) fn record_default<T: Component>(entity: Entity) {
+    record(entity, |world, from, to| {
+        let Some(component) = world.get_entity_mut(from).and_then(|mut e| e.take::<T>()) else {
+            return;
+        };
+        match world.get_entity_mut(to) {
+            Some(mut entity) if !entity.contains::<T>() => {
+                entity.insert(component);
+            }
+            _ => (),
+        }
+    })
+}
+
+/// Remove [`BevyObject`] `T` from the merge target.
+pub(crate) fn record_absent<T: BevyObject>(entity: Entity) {
+    record(entity, |world, _, to| {
+        if let Some(mut entity) = world.get_entity_mut(to) {
+            T::remove(&mut entity);
+        }
+    })
+}
+
+/// Despawn children of the merge target that satisfies [`BevyObject`] `T`.
+pub(crate) fn record_children<T: BevyObject>(entity: Entity) {
+    record(entity, |world, _, to| despawn_children::<T>(world, to))
+}
+
+/// Despawn children of an entity that satisfies [`BevyObject`] `T`.
+pub(crate) fn despawn_children<T: BevyObject>(world: &mut World, entity: Entity) {
+    let Some(children) = world.get::<Children>(entity) else {
+        return;
+    };
+    let queue: Vec<_> = children
+        .iter()
+        .filter(|e| world.get_entity(**e).is_some_and(|e| T::filter(&e)))
+        .copied()
+        .collect();
+    for entity in queue {
+        safe_despawn(world, entity);
+    }
+}
+
+/// Remove [`Parent`] from the merge target.
+pub(crate) fn record_orphan(entity: Entity) {
+    record(entity, |world, _, to| {
+        if let Some(mut entity) = world.get_entity_mut(to) {
+            entity.remove_parent();
+        }
+    })
+}
+
+/// Start recording operations on an entity.
+pub(crate) fn track(entity: Entity) {
+    MERGE_OPS.with(|ops| ops.borrow_mut().insert(entity, Tracked::default()));
+}
+
+/// Record a [`PersistentId`](crate::PersistentId) of a tracked entity
+/// that is registered to `other`, which is an error unless merged into `other`.
+pub(crate) fn record_duplicate(entity: Entity, id: u64, other: Entity) {
+    MERGE_OPS.with(|ops| {
+        if let Some(tracked) = ops.borrow_mut().get_mut(&entity) {
+            tracked.duplicates.push((id, other));
+        }
+    })
+}
+
+/// Returns an error if a tracked entity has a [`PersistentId`](crate::PersistentId)
+/// registered to an entity other than its merge target.
+pub(crate) fn check_duplicates(entity: Entity, target: Option<Entity>) -> Result<(), String> {
+    MERGE_OPS.with(|ops| {
+        let ops = ops.borrow();
+        let duplicates = ops.get(&entity).map(|x| x.duplicates.as_slice());
+        match duplicates
+            .into_iter()
+            .flatten()
+            .find(|(_, x)| Some(*x) != target)
+        {
+            Some((id, other)) => Err(persistent::duplicate_error(*id, *other)),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Returns true if operations on an entity are being recorded.
+pub(crate) fn is_tracked(entity: Entity) -> bool {
+    MERGE_OPS.with(|ops| ops.borrow().contains_key(&entity))
+}
+
+/// Stop recording operations on an entity and discard recorded operations.
+pub(crate) fn untrack(entity: Entity) {
+    MERGE_OPS.with(|ops| ops.borrow_mut().remove(&entity));
+}
+
+/// Stop recording operations on all entities.
+pub(crate) fn clear() {
+    MERGE_OPS.with(|ops| ops.borrow_mut().clear());
+}
+
+/// Merge a tracked entity into `target` and despawn it.
+///
+/// Children and [`Parent`] are moved to `target`,
+/// [`EntityId`](crate::EntityId)s pointing to `entity` now point to `target`.
+pub(crate) fn transfer(world: &mut World, entity: Entity, target: Entity) {
+    let tracked = MERGE_OPS
+        .with(|ops| ops.borrow_mut().remove(&entity))
+        .unwrap_or_default();
+    for op in tracked.ops {
+        op(world, entity, target);
+    }
+    transfer_reflected(world, entity, target);
+    if let Some(children) = world.get::<Children>(entity).map(|x| x.to_vec()) {
+        world.entity_mut(target).push_children(&children);
+    }
+    if let Some(parent) = world.get::<Parent>(entity).map(Parent::get) {
+        if parent != target && world.get::<Parent>(target).map(Parent::get) != Some(parent) {
+            world.entity_mut(target).set_parent(parent);
+        }
+    }
+    entity::remap(entity, target);
+    safe_despawn(world, entity);
+}
+
+/// Move components not moved by recorded operations to the merge target,
+/// if registered as [`ReflectComponent`] in the [`AppTypeRegistry`].
+///
+/// These are usually inserted by custom deserialize implementations.
+fn transfer_reflected(world: &mut World, entity: Entity, target: Entity) {
+    let Some(registry) = world.get_resource::<AppTypeRegistry>().cloned() else {
+        return;
+    };
+    let registry = registry.read();
+    let Some(entity_ref) = world.get_entity(entity) else {
+        return;
+    };
+    let values: Vec<_> = entity_ref
+        .archetype()
+        .components()
+        .filter_map(|id| world.components().get_info(id)?.type_id())
+        .filter(|id| *id != TypeId::of::<Parent>() && *id != TypeId::of::<Children>())
+        .filter_map(|id| {
+            let reflect = registry.get_type_data::<ReflectComponent>(id)?;
+            Some((reflect, reflect.reflect(entity_ref)?.clone_value()))
+        })
+        .collect();
+    let Some(mut target) = world.get_entity_mut(target) else {
+        return;
+    };
+    for (reflect, value) in values {
+        reflect.apply_or_insert(&mut target, value.as_ref(), &registry);
+    }
+}
+
+/// Make a [`BevyObject`] [`Deserialize`] by merging into existing entities matched by key `K`.
+pub(crate) struct MergeRoot<T, K>(PhantomData<(T, K)>);
+
+impl<'de, T: BevyObject, K: Component + Eq + Hash + Clone> Deserialize<'de> for MergeRoot<T, K> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        path_scope(PathSegment::Section(T::name()), || {
+            deserializer.deserialize_seq(MergeRoot(PhantomData))
+        })
+    }
+}
+
+impl<'de, T: BevyObject, K: Component + Eq + Hash + Clone> Visitor<'de> for MergeRoot<T, K> {
+    type Value = MergeRoot<T, K>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a sequence of entities")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let keys = with_world_mut(|world| {
+            let mut query = world.query_filtered::<(Entity, &K), T::Filter>();
+            query
+                .iter(world)
+                .map(|(entity, key)| (key.clone(), entity))
+                .collect::<FxHashMap<_, _>>()
+        })
+        .map_err(serde::de::Error::custom)?;
+        let keys = Rc::new(RefCell::new(keys));
+        let find = {
+            let keys = keys.clone();
+            move |world: &World, entity: Entity| {
+                let key = world.get::<K>(entity)?;
+                keys.borrow().get(key).copied()
+            }
+        };
+        // References to an object being merged point to its target once its key is known.
+        let previous = MERGE_TARGET.with(|f| f.replace(Some(Rc::new(find.clone()))));
+        let result = merge_seq::<T, K, A>(&mut seq, &keys, find);
+        MERGE_TARGET.with(|f| f.replace(previous));
+        result?;
+        Ok(MergeRoot(PhantomData))
+    }
+}
+
+/// Merge each object in a sequence into the entity with the same key `K` in `keys`.
+fn merge_seq<'de, T: BevyObject, K: Component + Eq + Hash + Clone, A: SeqAccess<'de>>(
+    seq: &mut A,
+    keys: &RefCell<FxHashMap<K, Entity>>,
+    find: impl Fn(&World, Entity) -> Option<Entity>,
+) -> Result<(), A::Error> {
+    for index in 0.. {
+        let entity = with_world_mut(|world| {
+            let entity = world.spawn_empty().id();
+            if let Some(mut root) = T::get_root(world) {
+                root.add_child(entity);
+            }
+            entity
+        })
+        .map_err(serde::de::Error::custom)?;
+        track(entity);
+        match path_scope(PathSegment::Index(index), || {
+            entity_scope(entity, || seq.next_element::<T::Object>())
+        }) {
+            Err(err) => {
+                untrack(entity);
+                with_world_mut(|world| safe_despawn(world, entity))
+                    .map_err(serde::de::Error::custom)?;
+                return Err(err);
+            }
+            Ok(None) => {
+                untrack(entity);
+                with_world_mut(|world| safe_despawn(world, entity))
+                    .map_err(serde::de::Error::custom)?;
+                break;
+            }
+            Ok(Some(_)) => {}
+        }
+        let referenced = MERGE_OPS.with(|ops| {
+            ops.borrow()
+                .get(&entity)
+                .is_some_and(|tracked| tracked.referenced)
+        });
+        with_world_mut(|world| {
+            let target = find(world, entity);
+            let error = match check_duplicates(entity, target) {
+                Err(err) => Some(err),
+                // References resolved before the key was deserialized point to `entity`.
+                Ok(()) if referenced && target.is_some() => Some(format!(
+                    "{} is referenced before its key {} is deserialized.",
+                    T::name(),
+                    type_name::<K>()
+                )),
+                Ok(()) => None,
+            };
+            if let Some(err) = error {
+                untrack(entity);
+                safe_despawn(world, entity);
+                return Err(serde::de::Error::custom(err));
+            }
+            match target {
+                Some(target) => transfer(world, entity, target),
+                None => {
+                    untrack(entity);
+                    if let Some(key) = world.get::<K>(entity).cloned() {
+                        keys.borrow_mut().insert(key, entity);
+                    }
+                }
+            }
+            Ok(())
+        })
+        .map_err(serde::de::Error::custom)??;
+    }
+    Ok(())
+}
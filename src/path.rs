@@ -0,0 +1,168 @@
+//! Module for locating deserialization errors in a save file.
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+};
+
+use serde::{Deserialize, Deserializer};
+
+/// A segment of an [`ObjectPath`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A section in a batch.
+    Section(&'static str),
+    /// An index in a sequence.
+    Index(usize),
+    /// A field of a [`BevyObject`](crate::BevyObject).
+    Field(&'static str),
+    /// A component or resource, as a type name.
+    Type(&'static str),
+    /// A type tag of a [`TypeTagged`](crate::typetagged::TypeTagged) value.
+    Tag(String),
+}
+
+/// Location of an item in a save file, like `Character[12].potions[3].Potion`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ObjectPath(pub Vec<PathSegment>);
+
+impl ObjectPath {
+    /// Returns true if the path has no segments.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns segments of the path.
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+}
+
+/// Remove module paths from a type name.
+fn short_name(name: &str) -> String {
+    let mut result = String::new();
+    let mut segment = String::new();
+    for c in name.chars() {
+        match c {
+            ':' => segment.clear(),
+            '<' | '>' | ',' | ' ' | '(' | ')' | '[' | ']' | '&' | ';' => {
+                result.push_str(&segment);
+                segment.clear();
+                result.push(c);
+            }
+            c => segment.push(c),
+        }
+    }
+    result.push_str(&segment);
+    result
+}
+
+impl Display for ObjectPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            if i > 0 && !matches!(segment, PathSegment::Index(_)) {
+                f.write_str(".")?;
+            }
+            match segment {
+                PathSegment::Section(name) | PathSegment::Field(name) => f.write_str(name)?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+                PathSegment::Type(name) => f.write_str(&short_name(name))?,
+                PathSegment::Tag(name) => f.write_str(name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error of a `load`, with the location of the error in the save file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadError<E> {
+    /// Location of the error, may be empty.
+    pub path: ObjectPath,
+    /// The original error.
+    pub error: E,
+}
+
+impl<E> LoadError<E> {
+    /// Convert the original error, keeping the path.
+    pub fn map<F>(self, f: impl FnOnce(E) -> F) -> LoadError<F> {
+        LoadError {
+            path: self.path,
+            error: f(self.error),
+        }
+    }
+}
+
+impl<E: Display> Display for LoadError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            self.error.fmt(f)
+        } else {
+            write!(f, "{}: {}", self.path, self.error)
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for LoadError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+thread_local! {
+    static PATH: RefCell<Vec<PathSegment>> = const { RefCell::new(Vec::new()) };
+    static ERROR_PATH: RefCell<Option<ObjectPath>> = const { RefCell::new(None) };
+}
+
+/// Run a function with a segment pushed to the current path,
+/// if it fails and no error was recorded, record the current path.
+pub(crate) fn path_scope<T, E>(
+    segment: PathSegment,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    PATH.with(|p| p.borrow_mut().push(segment));
+    let result = f();
+    PATH.with(|p| {
+        let mut path = p.borrow_mut();
+        ERROR_PATH.with(|e| {
+            let mut error = e.borrow_mut();
+            if result.is_err() {
+                error.get_or_insert_with(|| ObjectPath(path.clone()));
+            } else if error.as_ref().is_some_and(|e| e.0.len() >= path.len()) {
+                // An error inside this scope was recovered from.
+                *error = None;
+            }
+        });
+        path.pop();
+    });
+    result
+}
+
+/// Run a function with [`PathSegment::Type`] of `T` pushed to the current path.
+pub(crate) fn type_scope<T: ?Sized, U, E>(f: impl FnOnce() -> Result<U, E>) -> Result<U, E> {
+    path_scope(PathSegment::Type(std::any::type_name::<T>()), f)
+}
+
+/// Clear the current path and the recorded error path.
+pub(crate) fn clear() {
+    PATH.with(|p| p.borrow_mut().clear());
+    ERROR_PATH.with(|e| e.borrow_mut().take());
+}
+
+/// Attach the recorded error path to an error.
+pub(crate) fn with_path<E>(error: E) -> LoadError<E> {
+    LoadError {
+        path: ERROR_PATH
+            .with(|e| e.borrow_mut().take())
+            .unwrap_or_default(),
+        error,
+    }
+}
+
+/// Deserialize a field of a [`BevyObject`](crate::BevyObject), used in the derive macro.
+#[doc(hidden)]
+pub fn deserialize_field<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    name: &'static str,
+    deserializer: D,
+) -> Result<T, D::Error> {
+    path_scope(PathSegment::Field(name), || T::deserialize(deserializer))
+}
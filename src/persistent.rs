@@ -0,0 +1,362 @@
+//! Module for identifying entities across sessions.
+use bevy_ecs::{
+    component::{Component, ComponentHooks, StorageType},
+    entity::Entity,
+    query::With,
+    system::Resource,
+    world::{DeferredWorld, EntityWorldMut, World},
+};
+use bevy_serde_lens_core::{current_entity, with_world, with_world_mut};
+use ref_cast::RefCast;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    entity::{self, RefKey, PERSISTENT_MAP},
+    merge, world_entity_scope, world_entity_scope_mut, BindProject, BindProjectQuery, ZstInit,
+};
+
+/// A persistent identifier of an entity, unlike [`EntityId`](crate::EntityId),
+/// stays the same across sessions.
+///
+/// Ids are registered in the [`PersistentIds`] resource on insertion,
+/// `PersistentId::default()` is assigned a new id from [`PersistentIds`].
+///
+/// When used in a [`BevyObject`](crate::BevyObject), saves the id and
+/// registers it for [`PersistentPtr`] on load.
+/// With `#[serde(default)]`, objects saved without an id, or with `null`,
+/// are assigned a new id on load.
+///
+/// An id stays registered to the first entity it is inserted on.
+/// Loading an id registered to another entity in the world is an error,
+/// i.e. when loading the same save twice, unless the object is merged into that entity.
+///
+/// # Note
+///
+/// [`PersistentId`] should not be modified after insertion.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct PersistentId(pub u64);
+
+impl PersistentId {
+    /// Returns true if this id is not yet assigned.
+    pub fn is_unassigned(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl Component for PersistentId {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks
+            .on_insert(|mut world, entity, _| {
+                if world.get_resource::<PersistentIds>().is_some() {
+                    insert_hook(&mut world, entity);
+                } else {
+                    world.commands().add(move |world: &mut World| {
+                        world.init_resource::<PersistentIds>();
+                        if world.get::<PersistentId>(entity).is_some() {
+                            insert_hook(&mut world.into(), entity);
+                        }
+                    });
+                }
+            })
+            .on_remove(|mut world, entity, _| {
+                let Some(id) = world.get::<PersistentId>(entity).copied() else {
+                    return;
+                };
+                if let Some(mut ids) = world.get_resource_mut::<PersistentIds>() {
+                    if ids.map.get(&id.0) == Some(&entity) {
+                        ids.map.remove(&id.0);
+                    }
+                }
+            });
+    }
+}
+
+/// Assign an id if unassigned and register it.
+fn insert_hook(world: &mut DeferredWorld, entity: Entity) {
+    let Some(id) = world.get::<PersistentId>(entity).copied() else {
+        return;
+    };
+    let mut ids = world.resource_mut::<PersistentIds>();
+    let id = if id.is_unassigned() {
+        ids.allocate()
+    } else {
+        id
+    };
+    ids.next = ids.next.max(id.0 + 1);
+    let registered = ids.map.get(&id.0).copied();
+    // The first entity registered keeps the id.
+    if registered.is_none_or(|other| other == entity || world.get_entity(other).is_none()) {
+        world
+            .resource_mut::<PersistentIds>()
+            .map
+            .insert(id.0, entity);
+    }
+    if let Some(mut component) = world.get_mut::<PersistentId>(entity) {
+        if *component != id {
+            *component = id;
+        }
+    }
+}
+
+/// Registry of [`PersistentId`]s in the world, maintained automatically.
+///
+/// Insert this resource before spawning [`PersistentId`]s,
+/// otherwise registration is delayed until commands are applied.
+#[derive(Debug, Resource)]
+pub struct PersistentIds {
+    map: FxHashMap<u64, Entity>,
+    next: u64,
+}
+
+impl Default for PersistentIds {
+    fn default() -> Self {
+        Self {
+            map: FxHashMap::default(),
+            next: 1,
+        }
+    }
+}
+
+impl PersistentIds {
+    /// Obtain the [`Entity`] of a [`PersistentId`].
+    pub fn get(&self, id: PersistentId) -> Option<Entity> {
+        self.map.get(&id.0).copied()
+    }
+
+    /// Returns an unused [`PersistentId`], ids are never reused.
+    pub fn allocate(&mut self) -> PersistentId {
+        let id = self.next;
+        self.next += 1;
+        PersistentId(id)
+    }
+
+    /// Returns the number of registered ids.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if no ids are registered.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Iterate through registered ids and their entities.
+    pub fn iter(&self) -> impl Iterator<Item = (PersistentId, Entity)> + '_ {
+        self.map
+            .iter()
+            .map(|(id, entity)| (PersistentId(*id), *entity))
+    }
+}
+
+/// Insert a [`PersistentId`] on the current entity and register it for [`PersistentPtr`].
+fn insert_id(world: &mut World, entity: Entity, id: PersistentId) -> Option<()> {
+    world.init_resource::<PersistentIds>();
+    world.get_entity_mut(entity)?.insert(id);
+    merge::record_component::<PersistentId>(entity);
+    if !id.is_unassigned() {
+        let registered = entity::register(RefKey::Persistent(id.0), entity);
+        entity::insert_id(RefKey::Persistent(id.0), registered);
+    }
+    Some(())
+}
+
+/// Projection type of [`PersistentId`] in a [`BevyObject`](crate::BevyObject).
+#[doc(hidden)]
+pub struct SerializePersistentId(());
+
+impl ZstInit for SerializePersistentId {
+    fn init() -> Self {
+        SerializePersistentId(())
+    }
+}
+
+/// Here to make `#[serde(default)]` work.
+impl Default for SerializePersistentId {
+    fn default() -> Self {
+        let _ = with_world_mut(|world| {
+            let entity = current_entity().ok()?;
+            insert_id(world, entity, PersistentId::default())
+        });
+        SerializePersistentId(())
+    }
+}
+
+impl Serialize for SerializePersistentId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        world_entity_scope::<_, S>(|world, entity| match world.get::<PersistentId>(entity) {
+            Some(id) => id.serialize(serializer),
+            None => Err(serde::ser::Error::custom(format!(
+                "PersistentId missing: {entity:?}."
+            ))),
+        })?
+    }
+}
+
+/// Returns the entity `id` is registered to if it is not `entity`.
+fn find_duplicate(world: &World, entity: Entity, id: PersistentId) -> Option<Entity> {
+    if id.is_unassigned() {
+        return None;
+    }
+    world
+        .get_resource::<PersistentIds>()?
+        .get(id)
+        .filter(|other| *other != entity && world.get_entity(*other).is_some())
+}
+
+/// Error of loading a [`PersistentId`] registered to another entity.
+pub(crate) fn duplicate_error(id: u64, other: Entity) -> String {
+    format!("PersistentId {id} already exists on {other:?}.")
+}
+
+impl<'de> Deserialize<'de> for SerializePersistentId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Missing fields are deserialized as `None` by `serde`,
+        // non-self-describing formats always contain the id.
+        let id = if deserializer.is_human_readable() {
+            Option::<PersistentId>::deserialize(deserializer)?.unwrap_or_default()
+        } else {
+            PersistentId::deserialize(deserializer)?
+        };
+        world_entity_scope_mut::<_, D>(|world, entity| {
+            if let Some(other) = find_duplicate(world, entity, id) {
+                // Merged objects may carry the id of their merge target.
+                if merge::is_tracked(entity) {
+                    merge::record_duplicate(entity, id.0, other);
+                } else {
+                    return Err(serde::de::Error::custom(duplicate_error(id.0, other)));
+                }
+            }
+            insert_id(world, entity, id).ok_or_else(|| serde::de::Error::custom("Entity missing."))
+        })??;
+        Ok(SerializePersistentId(()))
+    }
+}
+
+impl BindProject for PersistentId {
+    type To = SerializePersistentId;
+    type Filter = With<PersistentId>;
+
+    fn remove(entity: &mut EntityWorldMut) {
+        entity.remove::<PersistentId>();
+    }
+}
+
+impl BindProjectQuery for PersistentId {
+    type Data = &'static PersistentId;
+}
+
+/// Find the entity of a [`PersistentId`], in the current batch, then in the world,
+/// then as a placeholder if references are deferred.
+fn get_entity<'de, D: Deserializer<'de>>(id: PersistentId) -> Result<Entity, D::Error> {
+    if let Some(entity) = PERSISTENT_MAP.with(|x| x.borrow().get(&id.0).copied()) {
+        return Ok(merge::redirect(entity));
+    }
+    with_world_mut(|world| world.get_resource::<PersistentIds>()?.get(id))
+        .ok()
+        .flatten()
+        .or_else(|| entity::placeholder(RefKey::Persistent(id.0)))
+        .ok_or_else(|| serde::de::Error::custom(format!("PersistentId {} not found.", id.0)))
+}
+
+/// Obtain the [`PersistentId`] of an entity in a serialize implementation.
+fn get_id<S: Serializer>(entity: Entity) -> Result<PersistentId, S::Error> {
+    with_world(|world| world.get::<PersistentId>(entity).copied())
+        .map_err(serde::ser::Error::custom)?
+        .ok_or_else(|| serde::ser::Error::custom(format!("PersistentId missing: {entity:?}.")))
+}
+
+/// Projection type of an [`Entity`] as its [`PersistentId`].
+///
+/// When used with `#[serde(with = "PersistentPtr")]`:
+///
+/// * On serialization: Save the [`PersistentId`] of this [`Entity`].
+/// * On deserialization: Find the [`Entity`] with this [`PersistentId`]
+///   in the batch or in the world.
+///
+/// # Errors
+///
+/// If the entity has no [`PersistentId`], or no entity with the [`PersistentId`] is found.
+#[derive(Debug, RefCast)]
+#[repr(transparent)]
+pub struct PersistentPtr(pub Entity);
+
+impl Serialize for PersistentPtr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        get_id::<S>(self.0)?.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PersistentPtr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = PersistentId::deserialize(deserializer)?;
+        Ok(PersistentPtr(get_entity::<D>(id)?))
+    }
+}
+
+impl PersistentPtr {
+    /// Serialize with [`PersistentPtr`].
+    pub fn serialize<S: Serializer>(item: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(PersistentPtr::ref_cast(item), serializer)
+    }
+
+    /// Deserialize with [`PersistentPtr`].
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
+        <PersistentPtr as Deserialize>::deserialize(deserializer).map(|x| x.0)
+    }
+}
+
+/// Projection type of an [`Option<Entity>`] as its [`PersistentId`].
+///
+/// When used with `#[serde(with = "OptionPersistentPtr")]`:
+///
+/// * On serialization: Save the [`PersistentId`] of this [`Entity`].
+/// * On deserialization: Find the [`Entity`] with this [`PersistentId`]
+///   in the batch or in the world.
+///
+/// # Errors
+///
+/// If the entity has no [`PersistentId`], or no entity with the [`PersistentId`] is found.
+#[derive(Debug, RefCast)]
+#[repr(transparent)]
+pub struct OptionPersistentPtr(pub Option<Entity>);
+
+impl Serialize for OptionPersistentPtr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Some(entity) => Some(get_id::<S>(entity)?).serialize(serializer),
+            None => None::<PersistentId>.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OptionPersistentPtr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match <Option<PersistentId>>::deserialize(deserializer)? {
+            Some(id) => Ok(OptionPersistentPtr(Some(get_entity::<D>(id)?))),
+            None => Ok(OptionPersistentPtr(None)),
+        }
+    }
+}
+
+impl OptionPersistentPtr {
+    /// Serialize with [`OptionPersistentPtr`].
+    pub fn serialize<S: Serializer>(
+        item: &Option<Entity>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(OptionPersistentPtr::ref_cast(item), serializer)
+    }
+
+    /// Deserialize with [`OptionPersistentPtr`].
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Entity>, D::Error> {
+        <OptionPersistentPtr as Deserialize>::deserialize(deserializer).map(|x| x.0)
+    }
+}
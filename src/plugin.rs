@@ -0,0 +1,283 @@
+//! Module for saving and loading on the local filesystem with events.
+use std::{
+    io::Write,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use bevy_app::{App, Last, Plugin};
+use bevy_ecs::{
+    event::{Event, Events, ManualEventReader},
+    system::Local,
+    world::World,
+};
+
+use crate::{path::LoadError, BatchSerialization, LoadReport, WorldExtension};
+
+/// Format of a save file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SaveFormat {
+    /// Pretty printed `json` via `serde_json`.
+    Json,
+    /// Pretty printed `ron`.
+    Ron,
+    /// Binary format via `postcard`.
+    Postcard,
+}
+
+/// Error of a [`SaveRequest<T>`] or [`LoadRequest<T>`].
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum FileError {
+    /// Error reading or writing the file.
+    #[error(transparent)]
+    Io(Arc<std::io::Error>),
+    /// Error of [`SaveFormat::Json`].
+    #[error(transparent)]
+    Json(Arc<serde_json::Error>),
+    /// Error of [`SaveFormat::Ron`].
+    #[error(transparent)]
+    Ron(ron::Error),
+    /// Error of [`SaveFormat::Postcard`].
+    #[error(transparent)]
+    Postcard(postcard::Error),
+}
+
+impl From<std::io::Error> for FileError {
+    fn from(error: std::io::Error) -> Self {
+        FileError::Io(Arc::new(error))
+    }
+}
+
+/// Request to save a [`BatchSerialization`] type to a file.
+///
+/// Handled by [`SerdeLensPlugin<T>`] at the end of the frame.
+/// The save is written to a temporary file first, then renamed to `path`,
+/// so an existing file is not corrupted if the save fails.
+#[derive(Debug, Clone, Event)]
+pub struct SaveRequest<T> {
+    /// Path of the file, parent directories are created if missing.
+    pub path: PathBuf,
+    /// Format of the file.
+    pub format: SaveFormat,
+    p: PhantomData<fn() -> T>,
+}
+
+impl<T> SaveRequest<T> {
+    /// Create a request to save `T` to `path` in `format`.
+    pub fn new(path: impl Into<PathBuf>, format: SaveFormat) -> Self {
+        SaveRequest {
+            path: path.into(),
+            format,
+            p: PhantomData,
+        }
+    }
+}
+
+/// Request to load a [`BatchSerialization`] type from a file.
+///
+/// Handled by [`SerdeLensPlugin<T>`] at the end of the frame.
+#[derive(Debug, Clone, Event)]
+pub struct LoadRequest<T> {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Format of the file.
+    pub format: SaveFormat,
+    p: PhantomData<fn() -> T>,
+}
+
+impl<T> LoadRequest<T> {
+    /// Create a request to load `T` from `path` in `format`.
+    pub fn new(path: impl Into<PathBuf>, format: SaveFormat) -> Self {
+        LoadRequest {
+            path: path.into(),
+            format,
+            p: PhantomData,
+        }
+    }
+}
+
+/// Sent when a [`SaveRequest<T>`] succeeds.
+#[derive(Debug, Clone, Event)]
+pub struct SaveCompleted<T> {
+    /// Path of the [`SaveRequest<T>`].
+    pub path: PathBuf,
+    p: PhantomData<fn() -> T>,
+}
+
+/// Sent when a [`SaveRequest<T>`] fails.
+#[derive(Debug, Clone, Event)]
+pub struct SaveFailed<T> {
+    /// Path of the [`SaveRequest<T>`].
+    pub path: PathBuf,
+    /// Serialization or io error.
+    pub error: FileError,
+    p: PhantomData<fn() -> T>,
+}
+
+/// Sent when a [`LoadRequest<T>`] succeeds.
+#[derive(Debug, Clone, Event)]
+pub struct LoadCompleted<T> {
+    /// Path of the [`LoadRequest<T>`].
+    pub path: PathBuf,
+    /// Report of the `load`.
+    pub report: LoadReport,
+    p: PhantomData<fn() -> T>,
+}
+
+/// Sent when a [`LoadRequest<T>`] fails.
+///
+/// Like `load`, nothing is loaded on failure.
+#[derive(Debug, Clone, Event)]
+pub struct LoadFailed<T> {
+    /// Path of the [`LoadRequest<T>`].
+    pub path: PathBuf,
+    /// Deserialization or io error, with the location of deserialization errors.
+    pub error: LoadError<FileError>,
+    p: PhantomData<fn() -> T>,
+}
+
+/// Plugin that handles [`SaveRequest<T>`] and [`LoadRequest<T>`] on the local filesystem.
+///
+/// Requests are handled in an exclusive system in [`Last`],
+/// results are reported by [`SaveCompleted<T>`], [`SaveFailed<T>`],
+/// [`LoadCompleted<T>`] and [`LoadFailed<T>`].
+pub struct SerdeLensPlugin<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for SerdeLensPlugin<T> {
+    fn default() -> Self {
+        SerdeLensPlugin(PhantomData)
+    }
+}
+
+impl<T: BatchSerialization + 'static> Plugin for SerdeLensPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveRequest<T>>()
+            .add_event::<LoadRequest<T>>()
+            .add_event::<SaveCompleted<T>>()
+            .add_event::<SaveFailed<T>>()
+            .add_event::<LoadCompleted<T>>()
+            .add_event::<LoadFailed<T>>()
+            .add_systems(Last, handle_requests::<T>);
+    }
+}
+
+fn handle_requests<T: BatchSerialization + 'static>(
+    world: &mut World,
+    mut saves: Local<ManualEventReader<SaveRequest<T>>>,
+    mut loads: Local<ManualEventReader<LoadRequest<T>>>,
+) {
+    let requests: Vec<_> = saves
+        .read(world.resource::<Events<SaveRequest<T>>>())
+        .map(|x| (x.path.clone(), x.format))
+        .collect();
+    for (path, format) in requests {
+        match save_file::<T>(world, &path, format) {
+            Ok(()) => {
+                world.send_event(SaveCompleted::<T> {
+                    path,
+                    p: PhantomData,
+                });
+            }
+            Err(error) => {
+                world.send_event(SaveFailed::<T> {
+                    path,
+                    error,
+                    p: PhantomData,
+                });
+            }
+        }
+    }
+    let requests: Vec<_> = loads
+        .read(world.resource::<Events<LoadRequest<T>>>())
+        .map(|x| (x.path.clone(), x.format))
+        .collect();
+    for (path, format) in requests {
+        match load_file::<T>(world, &path, format) {
+            Ok(report) => {
+                world.send_event(LoadCompleted::<T> {
+                    path,
+                    report,
+                    p: PhantomData,
+                });
+            }
+            Err(error) => {
+                world.send_event(LoadFailed::<T> {
+                    path,
+                    error,
+                    p: PhantomData,
+                });
+            }
+        }
+    }
+}
+
+fn save_file<T: BatchSerialization>(
+    world: &mut World,
+    path: &Path,
+    format: SaveFormat,
+) -> Result<(), FileError> {
+    let lens = world.serialize_lens::<T>();
+    let bytes = match format {
+        SaveFormat::Json => {
+            serde_json::to_vec_pretty(&lens).map_err(|e| FileError::Json(Arc::new(e)))?
+        }
+        SaveFormat::Ron => ron::ser::to_string_pretty(&lens, Default::default())
+            .map_err(FileError::Ron)?
+            .into_bytes(),
+        SaveFormat::Postcard => postcard::to_allocvec(&lens).map_err(FileError::Postcard)?,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let result = write_file(&temp, &bytes).and_then(|_| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    Ok(result?)
+}
+
+/// Write and flush a file to disk.
+fn write_file(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+fn load_file<T: BatchSerialization>(
+    world: &mut World,
+    path: &Path,
+    format: SaveFormat,
+) -> Result<LoadReport, LoadError<FileError>> {
+    let bytes = std::fs::read(path).map_err(|e| LoadError {
+        path: Default::default(),
+        error: FileError::from(e),
+    })?;
+    match format {
+        SaveFormat::Json => {
+            let mut deserializer = serde_json::Deserializer::from_slice(&bytes);
+            world
+                .load::<T, _>(&mut deserializer)
+                .map_err(|e| e.map(|e| FileError::Json(Arc::new(e))))
+        }
+        SaveFormat::Ron => {
+            let mut deserializer =
+                ron::Deserializer::from_bytes(&bytes).map_err(|e| LoadError {
+                    path: Default::default(),
+                    error: FileError::Ron(e.code),
+                })?;
+            world
+                .load::<T, _>(&mut deserializer)
+                .map_err(|e| e.map(FileError::Ron))
+        }
+        SaveFormat::Postcard => {
+            let mut deserializer = postcard::Deserializer::from_bytes(&bytes);
+            world
+                .load::<T, _>(&mut deserializer)
+                .map_err(|e| e.map(FileError::Postcard))
+        }
+    }
+}
@@ -1,14 +1,26 @@
-use crate::entity::EID_MAP;
+use crate::delta::{save_delta, track_delta, DeserializeDelta};
+use crate::entity::{
+    self, EntityMappings, EntityRefMode, EntityResolver, EntityResolvers, EID_MAP,
+};
+use crate::journal::{self, Journal};
+use crate::merge::MergeRoot;
+use crate::path::{self, LoadError};
 use crate::typetagged::{
     DeserializeAnyFn, IntoTypeTagged, TraitObject, TypeTagServer, TYPETAG_SERVER,
 };
-use crate::{de_scope, BatchSerialization};
+use crate::value::{self, OwnedSave, Value, ValueSerializer};
+use crate::versioning::{serialize_versioned, DeserializeVersioned, Migrations};
+use crate::{de_scope, despawn_empty_parents, safe_despawn, BatchSerialization, BevyObject};
 use bevy_app::App;
+use bevy_ecs::component::{Component, Tick};
+use bevy_ecs::entity::{Entity, MapEntities};
 use bevy_ecs::world::World;
-use serde::de::DeserializeSeed;
+use serde::de::{DeserializeOwned, DeserializeSeed};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::marker::PhantomData;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 #[allow(unused)]
 use crate::batch;
@@ -25,7 +37,26 @@ pub trait WorldExtension {
         &mut self,
         serializer: S,
     ) -> Result<S::Ok, S::Error>;
-    /// Load a [`BatchSerialization`] type.
+    /// Save a [`BatchSerialization`] type into an [`OwnedSave`] held in memory.
+    ///
+    /// The result can be encoded later without world access, i.e. on another thread,
+    /// and produces the same output as `save`.
+    /// Components are still serialized into a [`Value`] on the calling thread,
+    /// only encoding into the target format is deferred, see [`OwnedSave`] for details.
+    /// `human_readable` must match [`Serializer::is_human_readable`] of the target format,
+    /// i.e. `true` for `serde_json` and `false` for `postcard`.
+    fn save_owned<T: BatchSerialization>(
+        &mut self,
+        human_readable: bool,
+    ) -> Result<OwnedSave, value::Error>;
+    /// Load a [`BatchSerialization`] type, returns a [`LoadReport`] of spawned entities.
+    ///
+    /// Loading is all-or-nothing, on error, spawned entities are despawned and
+    /// inserted resources are reverted to their previous state.
+    /// The returned [`LoadError`] contains the [`ObjectPath`](crate::ObjectPath)
+    /// of the failing item, like `Character[12].potions[3].Potion`.
+    /// Side effects of custom deserialize implementations, like adding to an
+    /// [`Interner`](crate::interning::Interner), are not reverted.
     ///
     /// # What's a [`Deserializer`]?
     ///
@@ -34,16 +65,140 @@ pub trait WorldExtension {
     fn load<'de, T: BatchSerialization, D: Deserializer<'de>>(
         &mut self,
         deserializer: D,
-    ) -> Result<(), D::Error>;
+    ) -> Result<LoadReport, LoadError<D::Error>>;
+    /// Load a [`BatchSerialization`] type, replacing objects currently bound to it.
+    ///
+    /// New objects are loaded first, on success, previously bound entities are despawned
+    /// and resources missing from the save are removed.
+    /// Like `load`, on error, nothing is changed.
+    fn load_replace<'de, T: BatchSerialization, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<LoadReport, LoadError<D::Error>>;
+    /// Save a [`BatchSerialization`] type with a version number.
+    ///
+    /// This is serialized as a struct with fields `version` and `data`,
+    /// which can be loaded with `load_versioned`.
+    fn save_versioned<T: BatchSerialization, S: Serializer>(
+        &mut self,
+        version: u32,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+    /// Load a [`BatchSerialization`] type saved by `save_versioned`.
+    ///
+    /// `version` is the current version, saves newer than `version` are rejected.
+    /// Migrations registered by `register_migration` with a `from_version` greater than
+    /// or equal to the saved version and less than `version`
+    /// run in order before the data is deserialized.
+    /// Running migrations requires a self-describing format,
+    /// i.e. not `postcard`, but loading a save that needs no migration works on all formats.
+    ///
+    /// Like `load`, loading is all-or-nothing.
+    fn load_versioned<'de, T: BatchSerialization, D: Deserializer<'de>>(
+        &mut self,
+        version: u32,
+        deserializer: D,
+    ) -> Result<LoadReport, LoadError<D::Error>>;
+    /// Register a migration of a section from `from_version` to the next version.
+    ///
+    /// `section` is the name of an entry in a [`batch!`] or the name of a single
+    /// [`SerializeWorld`](crate::SerializeWorld) type. The function receives the section
+    /// as a [`Value`] and is skipped if the section is missing.
+    ///
+    /// # Example
+    /// ```
+    /// // version 1 to 2: `hp` was renamed to `health`.
+    /// world.register_migration("Character", 1, |value| {
+    ///     let Value::Seq(items) = value else {
+    ///         return Err("expected a sequence.".to_owned());
+    ///     };
+    ///     for item in items {
+    ///         if let Some(hp) = item.remove("hp") {
+    ///             item.insert("health", hp);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// });
+    /// ```
+    fn register_migration(
+        &mut self,
+        section: &str,
+        from_version: u32,
+        f: impl Fn(&mut Value) -> Result<(), String> + Send + Sync + 'static,
+    );
+    /// Load a [`BevyObject`] by merging into existing entities matched by key component `K`.
+    ///
+    /// For each deserialized object, if an entity satisfying the [`BevyObject`]'s filter
+    /// has an equal `K`, serialized components are overwritten in place,
+    /// missing [`Maybe`](crate::Maybe) components are removed and children extracted by
+    /// [`Child`](crate::Child) or [`ChildVec`](crate::ChildVec) are replaced.
+    /// Components not part of the [`BevyObject`] are kept.
+    /// Components inserted by custom deserialize implementations are moved to the matched
+    /// entity only if registered as [`ReflectComponent`](bevy_ecs::reflect::ReflectComponent)
+    /// in the [`AppTypeRegistry`](bevy_ecs::reflect::AppTypeRegistry).
+    ///
+    /// Objects without a match or without `K` are spawned as new entities.
+    ///
+    /// References to the [`EntityId`](crate::EntityId) of a matched object point to the matched entity,
+    /// referencing an object inside itself before its `K` is deserialized is an error.
+    ///
+    /// Unlike `load`, changes made before an error are not reverted.
+    fn load_merge<'de, T: BevyObject, K: Component + Eq + Hash + Clone, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<(), LoadError<D::Error>>;
+    /// Save [`BevyObject`]s changed since a [`Tick`], and keys `K` of objects despawned
+    /// since the same [`Tick`], which can be loaded with `apply_delta`.
+    ///
+    /// An object is written if any serialized component, including those on children,
+    /// is added or changed after `since`, or if components of the object or its descendants
+    /// are removed or its hierarchy changed after `since`, this may write some unchanged objects.
+    /// Objects without `K` are not part of the delta.
+    /// Use [`World::change_tick`] to obtain a tick for the next delta.
+    ///
+    /// Objects without added or changed components on themselves, their ancestors
+    /// or descendants are skipped without being serialized.
+    ///
+    /// Despawns are only recorded after `track_delta` or the first `save_delta`,
+    /// despawns recorded before `since` are discarded, so `since` should not decrease between calls.
+    /// In an `App`, removal events are cleared every frame, add [`record_removals`](crate::record_removals)
+    /// to the `Last` schedule so removals between saves are not missed.
+    fn save_delta<T: BevyObject, K: Component + Eq + Hash + Clone + Serialize, S: Serializer>(
+        &mut self,
+        since: Tick,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>;
+    /// Start recording despawned objects for `save_delta`.
+    ///
+    /// Call this before objects can be despawned, i.e. on startup.
+    fn track_delta<T: BevyObject, K: Component + Eq + Hash + Clone>(&mut self);
+    /// Apply a delta saved by `save_delta`.
+    ///
+    /// Changed objects are merged into entities matched by `K` like `load_merge`,
+    /// then entities with removed keys are despawned.
+    ///
+    /// Like `load_merge`, changes made before an error are not reverted.
+    fn apply_delta<
+        'de,
+        T: BevyObject,
+        K: Component + Eq + Hash + Clone + DeserializeOwned,
+        D: Deserializer<'de>,
+    >(
+        &mut self,
+        deserializer: D,
+    ) -> Result<(), LoadError<D::Error>>;
     /// Create a [`Serialize`] type from a [`World`] and a [`BatchSerialization`] type.
-    fn serialize_lens<S: BatchSerialization>(&mut self) -> SerializeLens<S>;
+    fn serialize_lens<S: BatchSerialization>(&mut self) -> SerializeLens<'_, S>;
     /// Create a [`DeserializeSeed`] type from a [`World`] and a [`BatchSerialization`] type.
-    fn deserialize_lens<S: BatchSerialization>(&mut self) -> DeserializeLens<S>;
+    fn deserialize_lens<S: BatchSerialization>(&mut self) -> DeserializeLens<'_, S>;
     /// Create a [`Deserialize`] scope from a [`World`].
     ///
     /// [`InWorld`] can be used inside the scope.
     fn deserialize_scope<T>(&mut self, f: impl FnOnce() -> T) -> T;
     /// Despawn all entities in a [`BatchSerialization`] type recursively.
+    ///
+    /// Parents spawned by [`ParentObject`](crate::ParentObject)s are despawned
+    /// if they have no children left.
     fn despawn_bound_objects<T: BatchSerialization>(&mut self);
     /// Register a type that can be deserialized dynamically.
     fn register_typetag<A: TraitObject, B: IntoTypeTagged<A>>(&mut self);
@@ -57,6 +212,14 @@ pub trait WorldExtension {
     /// app.register_deserialize_any(|x: i64| Ok(DefaultAttack::new(x as i32)));
     /// ```
     fn register_deserialize_any<T: TraitObject, O>(&mut self, f: impl DeserializeAnyFn<T, O>);
+    /// Register an [`EntityResolver`] for references to entities outside of a batch.
+    fn register_entity_resolver(&mut self, resolver: impl EntityResolver);
+    /// Remap [`Entity`]s in component `T` via [`MapEntities`] after `load`.
+    ///
+    /// This allows `T` to serialize [`Entity`]s directly instead of using `EntityPtr`,
+    /// references must be [`EntityId`](crate::EntityId)s in the same batch
+    /// or resolved by an [`EntityResolver`].
+    fn register_map_entities<T: Component + MapEntities>(&mut self);
 }
 
 impl WorldExtension for World {
@@ -64,27 +227,135 @@ impl WorldExtension for World {
         &mut self,
         serializer: S,
     ) -> Result<S::Ok, S::Error> {
-        T::serialize(self, serializer)
+        let human_readable = serializer.is_human_readable();
+        entity::save_scope(
+            self,
+            |world| collect_ids::<T>(world, human_readable),
+            |world| T::serialize(world, serializer),
+        )
+    }
+
+    fn save_owned<T: BatchSerialization>(
+        &mut self,
+        human_readable: bool,
+    ) -> Result<OwnedSave, value::Error> {
+        let value = entity::save_scope(
+            self,
+            |world| collect_ids::<T>(world, human_readable),
+            |world| T::serialize(world, ValueSerializer { human_readable }),
+        )?;
+        Ok(OwnedSave {
+            value,
+            human_readable,
+        })
     }
 
     fn load<'de, T: BatchSerialization, D: Deserializer<'de>>(
         &mut self,
         deserializer: D,
-    ) -> Result<(), D::Error> {
-        EID_MAP.with(|m| m.borrow_mut().clear());
-        self.init_resource::<TypeTagServer>();
-        self.resource_scope::<TypeTagServer, _>(|world, server| {
-            TYPETAG_SERVER.set(&server, || {
-                de_scope(world, || T::De::deserialize(deserializer)).map(|_| ())
-            })
+    ) -> Result<LoadReport, LoadError<D::Error>> {
+        journal::transaction(self, |world| {
+            load_scope(world, true, || T::De::deserialize(deserializer).map(|_| ()))
         })
+        .map(|((), journal)| LoadReport::new(journal))
+    }
+
+    fn load_replace<'de, T: BatchSerialization, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<LoadReport, LoadError<D::Error>> {
+        let previous = T::bound_entities(self);
+        let ((), journal) = journal::transaction(self, |world| {
+            load_scope(world, true, || T::De::deserialize(deserializer).map(|_| ()))
+        })?;
+        // A single section is always present.
+        if T::LEN == 1 {
+            T::despawn_missing(self, &T::names());
+        } else {
+            T::despawn_missing(self, &journal.sections);
+        }
+        for entity in previous {
+            safe_despawn(self, entity);
+        }
+        despawn_empty_parents(self);
+        Ok(LoadReport::new(journal))
+    }
+
+    fn save_versioned<T: BatchSerialization, S: Serializer>(
+        &mut self,
+        version: u32,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_versioned::<T>(self, version).serialize(serializer)
     }
 
-    fn serialize_lens<S: BatchSerialization>(&mut self) -> SerializeLens<S> {
+    fn load_versioned<'de, T: BatchSerialization, D: Deserializer<'de>>(
+        &mut self,
+        version: u32,
+        deserializer: D,
+    ) -> Result<LoadReport, LoadError<D::Error>> {
+        let seed = DeserializeVersioned::<T>::new(self, version);
+        journal::transaction(self, |world| {
+            load_scope(world, true, || seed.deserialize(deserializer))
+        })
+        .map(|((), journal)| LoadReport::new(journal))
+    }
+
+    fn register_migration(
+        &mut self,
+        section: &str,
+        from_version: u32,
+        f: impl Fn(&mut Value) -> Result<(), String> + Send + Sync + 'static,
+    ) {
+        let mut migrations = self.get_resource_or_insert_with(Migrations::default);
+        migrations.register(section, from_version, f)
+    }
+
+    fn load_merge<'de, T: BevyObject, K: Component + Eq + Hash + Clone, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<(), LoadError<D::Error>> {
+        load_scope(self, false, || {
+            MergeRoot::<T, K>::deserialize(deserializer).map(|_| ())
+        })
+    }
+
+    fn save_delta<T: BevyObject, K: Component + Eq + Hash + Clone + Serialize, S: Serializer>(
+        &mut self,
+        since: Tick,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        // `EntityId`s of unchanged objects are not part of the delta.
+        entity::save_scope(
+            self,
+            |_| (),
+            |world| save_delta::<T, K, S>(world, since, serializer),
+        )
+    }
+
+    fn track_delta<T: BevyObject, K: Component + Eq + Hash + Clone>(&mut self) {
+        track_delta::<T, K>(self)
+    }
+
+    fn apply_delta<
+        'de,
+        T: BevyObject,
+        K: Component + Eq + Hash + Clone + DeserializeOwned,
+        D: Deserializer<'de>,
+    >(
+        &mut self,
+        deserializer: D,
+    ) -> Result<(), LoadError<D::Error>> {
+        load_scope(self, false, || {
+            DeserializeDelta::<T, K>::deserialize(deserializer).map(|_| ())
+        })
+    }
+
+    fn serialize_lens<S: BatchSerialization>(&mut self) -> SerializeLens<'_, S> {
         SerializeLens(Mutex::new(self), PhantomData)
     }
 
-    fn deserialize_lens<S: BatchSerialization>(&mut self) -> DeserializeLens<S> {
+    fn deserialize_lens<S: BatchSerialization>(&mut self) -> DeserializeLens<'_, S> {
         DeserializeLens(self, PhantomData)
     }
 
@@ -93,7 +364,8 @@ impl WorldExtension for World {
     }
 
     fn despawn_bound_objects<T: BatchSerialization>(&mut self) {
-        T::despawn(self)
+        T::despawn(self);
+        despawn_empty_parents(self);
     }
 
     fn register_typetag<A: TraitObject, B: IntoTypeTagged<A>>(&mut self) {
@@ -105,6 +377,16 @@ impl WorldExtension for World {
         let mut server = self.get_resource_or_insert_with(TypeTagServer::default);
         server.register_deserialize_any::<T, O>(f)
     }
+
+    fn register_entity_resolver(&mut self, resolver: impl EntityResolver) {
+        let mut resolvers = self.get_resource_or_insert_with(EntityResolvers::default);
+        resolvers.0.push(Arc::new(resolver))
+    }
+
+    fn register_map_entities<T: Component + MapEntities>(&mut self) {
+        self.get_resource_or_insert_with(EntityMappings::default)
+            .register::<T>()
+    }
 }
 
 impl WorldExtension for App {
@@ -115,18 +397,90 @@ impl WorldExtension for App {
         self.world_mut().save::<T, S>(serializer)
     }
 
+    fn save_owned<T: BatchSerialization>(
+        &mut self,
+        human_readable: bool,
+    ) -> Result<OwnedSave, value::Error> {
+        self.world_mut().save_owned::<T>(human_readable)
+    }
+
     fn load<'de, T: BatchSerialization, D: Deserializer<'de>>(
         &mut self,
         deserializer: D,
-    ) -> Result<(), D::Error> {
+    ) -> Result<LoadReport, LoadError<D::Error>> {
         self.world_mut().load::<T, D>(deserializer)
     }
 
-    fn serialize_lens<S: BatchSerialization>(&mut self) -> SerializeLens<S> {
+    fn load_replace<'de, T: BatchSerialization, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<LoadReport, LoadError<D::Error>> {
+        self.world_mut().load_replace::<T, D>(deserializer)
+    }
+
+    fn save_versioned<T: BatchSerialization, S: Serializer>(
+        &mut self,
+        version: u32,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.world_mut().save_versioned::<T, S>(version, serializer)
+    }
+
+    fn load_versioned<'de, T: BatchSerialization, D: Deserializer<'de>>(
+        &mut self,
+        version: u32,
+        deserializer: D,
+    ) -> Result<LoadReport, LoadError<D::Error>> {
+        self.world_mut()
+            .load_versioned::<T, D>(version, deserializer)
+    }
+
+    fn register_migration(
+        &mut self,
+        section: &str,
+        from_version: u32,
+        f: impl Fn(&mut Value) -> Result<(), String> + Send + Sync + 'static,
+    ) {
+        self.world_mut()
+            .register_migration(section, from_version, f)
+    }
+
+    fn load_merge<'de, T: BevyObject, K: Component + Eq + Hash + Clone, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<(), LoadError<D::Error>> {
+        self.world_mut().load_merge::<T, K, D>(deserializer)
+    }
+
+    fn save_delta<T: BevyObject, K: Component + Eq + Hash + Clone + Serialize, S: Serializer>(
+        &mut self,
+        since: Tick,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.world_mut().save_delta::<T, K, S>(since, serializer)
+    }
+
+    fn track_delta<T: BevyObject, K: Component + Eq + Hash + Clone>(&mut self) {
+        self.world_mut().track_delta::<T, K>()
+    }
+
+    fn apply_delta<
+        'de,
+        T: BevyObject,
+        K: Component + Eq + Hash + Clone + DeserializeOwned,
+        D: Deserializer<'de>,
+    >(
+        &mut self,
+        deserializer: D,
+    ) -> Result<(), LoadError<D::Error>> {
+        self.world_mut().apply_delta::<T, K, D>(deserializer)
+    }
+
+    fn serialize_lens<S: BatchSerialization>(&mut self) -> SerializeLens<'_, S> {
         self.world_mut().serialize_lens()
     }
 
-    fn deserialize_lens<S: BatchSerialization>(&mut self) -> DeserializeLens<S> {
+    fn deserialize_lens<S: BatchSerialization>(&mut self) -> DeserializeLens<'_, S> {
         self.world_mut().deserialize_lens()
     }
 
@@ -145,6 +499,109 @@ impl WorldExtension for App {
     fn register_deserialize_any<T: TraitObject, O>(&mut self, f: impl DeserializeAnyFn<T, O>) {
         self.world_mut().register_deserialize_any::<T, O>(f)
     }
+
+    fn register_entity_resolver(&mut self, resolver: impl EntityResolver) {
+        self.world_mut().register_entity_resolver(resolver)
+    }
+
+    fn register_map_entities<T: Component + MapEntities>(&mut self) {
+        self.world_mut().register_map_entities::<T>()
+    }
+}
+
+/// Find all [`EntityId`](crate::EntityId)s in a save.
+fn collect_ids<T: BatchSerialization>(world: &mut World, human_readable: bool) {
+    let _ = T::serialize(world, ValueSerializer { human_readable });
+}
+
+/// Setup the `load` scope with the [`TypeTagServer`] and a cleared [`EntityId`](crate::EntityId) map,
+/// errors are annotated with their [`ObjectPath`](crate::ObjectPath).
+///
+/// If `deferred` is set, references are resolved according to [`EntityRefMode`].
+fn load_scope<T, E: serde::de::Error>(
+    world: &mut World,
+    deferred: bool,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, LoadError<E>> {
+    entity::clear_ids();
+    path::clear();
+    let deferred =
+        deferred && world.get_resource::<EntityRefMode>() == Some(&EntityRefMode::Deferred);
+    entity::deferred_scope(world, deferred, |world| {
+        typetag_scope(world, || entity::map_entities_scope(f))
+    })
+    .map_err(path::with_path)
+}
+
+/// Setup the `deserialize` scope with the [`TypeTagServer`].
+pub(crate) fn typetag_scope<T>(world: &mut World, f: impl FnOnce() -> T) -> T {
+    world.init_resource::<TypeTagServer>();
+    world.resource_scope::<TypeTagServer, _>(|world, server| {
+        TYPETAG_SERVER.set(&server, || de_scope(world, f))
+    })
+}
+
+/// Summary of a successful `load`.
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    /// Root entities spawned by each section, in order.
+    pub entities: HashMap<&'static str, Vec<Entity>>,
+    /// Parents spawned by [`ParentObject`](crate::ParentObject)s, in order.
+    pub parents: Vec<Entity>,
+    /// Names of resources inserted.
+    pub resources: Vec<&'static str>,
+    /// Map of serialized [`EntityId`](crate::EntityId)s to spawned entities.
+    pub entity_map: HashMap<u64, Entity>,
+    /// Map of [`EntityId`](crate::EntityId)s serialized as labels to spawned entities.
+    pub labels: HashMap<String, Entity>,
+    /// Names of unknown sections skipped by [`SectionPolicy`](crate::SectionPolicy).
+    pub unknown_sections: Vec<String>,
+}
+
+impl LoadReport {
+    fn new(journal: Journal) -> Self {
+        let mut entities = HashMap::<_, Vec<_>>::new();
+        for (section, entity) in journal.spawned {
+            entities.entry(section).or_default().push(entity);
+        }
+        LoadReport {
+            entities,
+            parents: journal.parents,
+            resources: journal.resources,
+            entity_map: EID_MAP.with(|m| {
+                m.borrow()
+                    .iter()
+                    .filter(|(k, _)| entity::get_label(**k).is_none())
+                    .map(|(k, v)| (entity::file_id(*k), *v))
+                    .collect()
+            }),
+            labels: EID_MAP.with(|m| {
+                m.borrow()
+                    .iter()
+                    .filter_map(|(k, v)| Some((entity::get_label(*k)?, *v)))
+                    .collect()
+            }),
+            unknown_sections: journal.unknown_sections,
+        }
+    }
+
+    /// Returns root entities spawned by a section.
+    pub fn get(&self, section: &str) -> &[Entity] {
+        self.entities
+            .get(section)
+            .map(|x| x.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns the entity spawned for a serialized [`EntityId`](crate::EntityId).
+    pub fn entity(&self, id: u64) -> Option<Entity> {
+        self.entity_map.get(&id).copied()
+    }
+
+    /// Returns the entity spawned for an [`EntityId`](crate::EntityId) serialized as a label.
+    pub fn labeled(&self, label: &str) -> Option<Entity> {
+        self.labels.get(label).copied()
+    }
 }
 
 /// A [`Serialize`] type from a [`World`] reference and a [`BatchSerialization`] type.
@@ -163,14 +620,16 @@ impl<T: BatchSerialization> Serialize for SerializeLens<'_, T> {
 pub struct DeserializeLens<'t, S: BatchSerialization>(&'t mut World, PhantomData<S>);
 
 impl<'de, T: BatchSerialization> DeserializeSeed<'de> for DeserializeLens<'de, T> {
-    type Value = ();
+    type Value = LoadReport;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        EID_MAP.with(|m| m.borrow_mut().clear());
-        self.0.load::<T, D>(deserializer)
+        entity::clear_ids();
+        self.0
+            .load::<T, D>(deserializer)
+            .map_err(serde::de::Error::custom)
     }
 }
 
@@ -184,7 +643,7 @@ impl<'de, T: BatchSerialization> Deserialize<'de> for InWorld<T> {
     where
         D: Deserializer<'de>,
     {
-        EID_MAP.with(|m| m.borrow_mut().clear());
+        entity::clear_ids();
         T::De::deserialize(deserializer)?;
         Ok(Self(PhantomData))
     }
@@ -75,6 +75,7 @@
 //! will never call `deserialize_any`. Use [`AnyTagged`] to use `deserialize_any`
 //! on primitives.
 
+use crate::path::{path_scope, PathSegment};
 use bevy_ecs::system::Resource;
 use bevy_reflect::TypePath;
 use erased_serde::Deserializer;
@@ -502,7 +503,9 @@ impl<'de, V: TraitObject> Visitor<'de> for TypeTaggedVisitor<'de, V> {
                 key,
             )));
         };
-        map.next_value_seed(DeserializeFnSeed(de_fn, PhantomData))
+        path_scope(PathSegment::Tag(key.to_string()), || {
+            map.next_value_seed(DeserializeFnSeed(de_fn, PhantomData))
+        })
     }
 
     fn visit_unit<E>(self) -> Result<Self::Value, E>
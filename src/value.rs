@@ -0,0 +1,1181 @@
+//! Module for an owned, format-agnostic representation of serialized data.
+//!
+//! A [`Value`] records the exact sequence of `serde` calls made by a [`Serialize`]
+//! implementation, it can be serialized again with any [`Serializer`]
+//! and produces the same output as the original type.
+//!
+//! A [`Value`] can also be deserialized from a self-describing format,
+//! modified, then deserialized into a type with [`ValueDeserializer`].
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::ser::{
+    SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An owned, format-agnostic representation of serialized data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Value>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant {
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    },
+    NewtypeStruct(&'static str, Box<Value>),
+    NewtypeVariant {
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: Box<Value>,
+    },
+    Seq(Vec<Value>),
+    Tuple(Vec<Value>),
+    TupleStruct(&'static str, Vec<Value>),
+    TupleVariant {
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        fields: Vec<Value>,
+    },
+    Map(Vec<(Value, Value)>),
+    /// Fields that are `None` are skipped.
+    Struct(&'static str, Vec<(&'static str, Option<Value>)>),
+    /// Fields that are `None` are skipped.
+    StructVariant {
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        fields: Vec<(&'static str, Option<Value>)>,
+    },
+}
+
+/// Error of [`Value`] conversions.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{0}")]
+pub struct Error(pub String);
+
+impl serde::ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl Value {
+    /// Convert a [`Serialize`] type to a [`Value`].
+    ///
+    /// `human_readable` should match the format the [`Value`] will be serialized to.
+    pub fn from_serialize<T: Serialize + ?Sized>(
+        item: &T,
+        human_readable: bool,
+    ) -> Result<Value, Error> {
+        item.serialize(ValueSerializer { human_readable })
+    }
+
+    /// Convert a [`Value`] to a [`Deserialize`] type.
+    ///
+    /// `human_readable` should match the format the [`Value`] was created from.
+    pub fn deserialize_into<T: DeserializeOwned>(self, human_readable: bool) -> Result<T, Error> {
+        T::deserialize(ValueDeserializer::new(self, human_readable))
+    }
+
+    /// Obtain a field of a map or a struct by name.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| k.as_str() == Some(key))
+                .map(|(_, v)| v),
+            Value::Struct(_, fields) | Value::StructVariant { fields, .. } => fields
+                .iter()
+                .find(|(k, _)| *k == key)
+                .and_then(|(_, v)| v.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Obtain a field of a map or a struct by name.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        match self {
+            Value::Map(entries) => entries
+                .iter_mut()
+                .find(|(k, _)| k.as_str() == Some(key))
+                .map(|(_, v)| v),
+            Value::Struct(_, fields) | Value::StructVariant { fields, .. } => fields
+                .iter_mut()
+                .find(|(k, _)| *k == key)
+                .and_then(|(_, v)| v.as_mut()),
+            _ => None,
+        }
+    }
+
+    /// Insert an entry to a map, returns the previous value if exists.
+    ///
+    /// Does nothing if not a [`Value::Map`].
+    pub fn insert(&mut self, key: impl Into<String>, value: Value) -> Option<Value> {
+        let Value::Map(entries) = self else {
+            return None;
+        };
+        let key = key.into();
+        match entries.iter_mut().find(|(k, _)| k.as_str() == Some(&key)) {
+            Some((_, v)) => Some(std::mem::replace(v, value)),
+            None => {
+                entries.push((Value::String(key), value));
+                None
+            }
+        }
+    }
+
+    /// Remove an entry from a map by name.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let Value::Map(entries) = self else {
+            return None;
+        };
+        let index = entries.iter().position(|(k, _)| k.as_str() == Some(key))?;
+        Some(entries.remove(index).1)
+    }
+
+    /// Returns the string if is a [`Value::String`].
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::I8(v) => serializer.serialize_i8(*v),
+            Value::I16(v) => serializer.serialize_i16(*v),
+            Value::I32(v) => serializer.serialize_i32(*v),
+            Value::I64(v) => serializer.serialize_i64(*v),
+            Value::I128(v) => serializer.serialize_i128(*v),
+            Value::U8(v) => serializer.serialize_u8(*v),
+            Value::U16(v) => serializer.serialize_u16(*v),
+            Value::U32(v) => serializer.serialize_u32(*v),
+            Value::U64(v) => serializer.serialize_u64(*v),
+            Value::U128(v) => serializer.serialize_u128(*v),
+            Value::F32(v) => serializer.serialize_f32(*v),
+            Value::F64(v) => serializer.serialize_f64(*v),
+            Value::Char(v) => serializer.serialize_char(*v),
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Bytes(v) => serializer.serialize_bytes(v),
+            Value::None => serializer.serialize_none(),
+            Value::Some(v) => serializer.serialize_some(v.as_ref()),
+            Value::Unit => serializer.serialize_unit(),
+            Value::UnitStruct(name) => serializer.serialize_unit_struct(name),
+            Value::UnitVariant {
+                name,
+                index,
+                variant,
+            } => serializer.serialize_unit_variant(name, *index, variant),
+            Value::NewtypeStruct(name, v) => serializer.serialize_newtype_struct(name, v.as_ref()),
+            Value::NewtypeVariant {
+                name,
+                index,
+                variant,
+                value,
+            } => serializer.serialize_newtype_variant(name, *index, variant, value.as_ref()),
+            Value::Seq(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Value::Tuple(items) => {
+                let mut seq = serializer.serialize_tuple(items.len())?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            Value::TupleStruct(name, items) => {
+                let mut seq = serializer.serialize_tuple_struct(name, items.len())?;
+                for item in items {
+                    seq.serialize_field(item)?;
+                }
+                seq.end()
+            }
+            Value::TupleVariant {
+                name,
+                index,
+                variant,
+                fields,
+            } => {
+                let mut seq =
+                    serializer.serialize_tuple_variant(name, *index, variant, fields.len())?;
+                for item in fields {
+                    seq.serialize_field(item)?;
+                }
+                seq.end()
+            }
+            Value::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Value::Struct(name, fields) => {
+                let mut st = serializer.serialize_struct(name, fields.len())?;
+                for (key, value) in fields {
+                    match value {
+                        Some(value) => st.serialize_field(key, value)?,
+                        None => st.skip_field(key)?,
+                    }
+                }
+                st.end()
+            }
+            Value::StructVariant {
+                name,
+                index,
+                variant,
+                fields,
+            } => {
+                let mut st =
+                    serializer.serialize_struct_variant(name, *index, variant, fields.len())?;
+                for (key, value) in fields {
+                    match value {
+                        Some(value) => st.serialize_field(key, value)?,
+                        None => st.skip_field(key)?,
+                    }
+                }
+                st.end()
+            }
+        }
+    }
+}
+
+/// A [`Serializer`] that outputs a [`Value`].
+#[derive(Debug, Clone, Copy)]
+pub struct ValueSerializer {
+    /// Value of [`Serializer::is_human_readable`].
+    pub human_readable: bool,
+}
+
+impl Default for ValueSerializer {
+    fn default() -> Self {
+        ValueSerializer {
+            human_readable: true,
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeSeqValue {
+    human_readable: bool,
+    kind: SeqKind,
+    items: Vec<Value>,
+}
+
+enum SeqKind {
+    Seq,
+    Tuple,
+    TupleStruct(&'static str),
+    TupleVariant(&'static str, u32, &'static str),
+}
+
+#[doc(hidden)]
+pub struct SerializeMapValue {
+    human_readable: bool,
+    key: Option<Value>,
+    entries: Vec<(Value, Value)>,
+}
+
+#[doc(hidden)]
+pub struct SerializeStructValue {
+    human_readable: bool,
+    variant: Option<(u32, &'static str)>,
+    name: &'static str,
+    fields: Vec<(&'static str, Option<Value>)>,
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SerializeSeqValue;
+    type SerializeTuple = SerializeSeqValue;
+    type SerializeTupleStruct = SerializeSeqValue;
+    type SerializeTupleVariant = SerializeSeqValue;
+    type SerializeMap = SerializeMapValue;
+    type SerializeStruct = SerializeStructValue;
+    type SerializeStructVariant = SerializeStructValue;
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(Value::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(Value::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(Value::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(Value::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        Ok(Value::I128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(Value::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(Value::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(Value::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(Value::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        Ok(Value::U128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        Ok(Value::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(Value::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::String(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        Ok(Value::Bytes(v.to_owned()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        Ok(Value::Some(Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Value, Error> {
+        Ok(Value::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(Value::UnitVariant {
+            name,
+            index,
+            variant,
+        })
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        Ok(Value::NewtypeStruct(name, Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        Ok(Value::NewtypeVariant {
+            name,
+            index,
+            variant,
+            value: Box::new(value.serialize(self)?),
+        })
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeSeqValue, Error> {
+        Ok(SerializeSeqValue {
+            human_readable: self.human_readable,
+            kind: SeqKind::Seq,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeSeqValue, Error> {
+        Ok(SerializeSeqValue {
+            human_readable: self.human_readable,
+            kind: SeqKind::Tuple,
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeSeqValue, Error> {
+        Ok(SerializeSeqValue {
+            human_readable: self.human_readable,
+            kind: SeqKind::TupleStruct(name),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeSeqValue, Error> {
+        Ok(SerializeSeqValue {
+            human_readable: self.human_readable,
+            kind: SeqKind::TupleVariant(name, index, variant),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMapValue, Error> {
+        Ok(SerializeMapValue {
+            human_readable: self.human_readable,
+            key: None,
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeStructValue, Error> {
+        Ok(SerializeStructValue {
+            human_readable: self.human_readable,
+            variant: None,
+            name,
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeStructValue, Error> {
+        Ok(SerializeStructValue {
+            human_readable: self.human_readable,
+            variant: Some((index, variant)),
+            name,
+            fields: Vec::with_capacity(len),
+        })
+    }
+}
+
+impl SerializeSeqValue {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(ValueSerializer {
+            human_readable: self.human_readable,
+        })?);
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        match self.kind {
+            SeqKind::Seq => Value::Seq(self.items),
+            SeqKind::Tuple => Value::Tuple(self.items),
+            SeqKind::TupleStruct(name) => Value::TupleStruct(name, self.items),
+            SeqKind::TupleVariant(name, index, variant) => Value::TupleVariant {
+                name,
+                index,
+                variant,
+                fields: self.items,
+            },
+        }
+    }
+}
+
+impl SerializeSeq for SerializeSeqValue {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTuple for SerializeSeqValue {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleStruct for SerializeSeqValue {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleVariant for SerializeSeqValue {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeMap for SerializeMapValue {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(ValueSerializer {
+            human_readable: self.human_readable,
+        })?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let Some(key) = self.key.take() else {
+            return Err(Error(
+                "serialize_value called before serialize_key.".to_owned(),
+            ));
+        };
+        let value = value.serialize(ValueSerializer {
+            human_readable: self.human_readable,
+        })?;
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Map(self.entries))
+    }
+}
+
+impl SerializeStructValue {
+    fn finish(self) -> Value {
+        match self.variant {
+            None => Value::Struct(self.name, self.fields),
+            Some((index, variant)) => Value::StructVariant {
+                name: self.name,
+                index,
+                variant,
+                fields: self.fields,
+            },
+        }
+    }
+}
+
+impl SerializeStruct for SerializeStructValue {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let value = value.serialize(ValueSerializer {
+            human_readable: self.human_readable,
+        })?;
+        self.fields.push((key, Some(value)));
+        Ok(())
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Error> {
+        self.fields.push((key, None));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeStructVariant for SerializeStructValue {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Error> {
+        SerializeStruct::skip_field(self, key)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+/// A save of a [`BatchSerialization`](crate::BatchSerialization) type held in memory,
+/// created by [`WorldExtension::save_owned`](crate::WorldExtension::save_owned).
+///
+/// Since no world access is needed, this can be encoded later or on another thread
+/// and produces the same output as [`WorldExtension::save`](crate::WorldExtension::save).
+///
+/// # Cost
+///
+/// Creating an [`OwnedSave`] runs the full serde walk of every component into a [`Value`] tree
+/// on the calling thread, which costs about as much as a `save` into memory.
+/// Only encoding the [`Value`] into the target format, i.e. formatting text,
+/// compressing or writing to disk, can be moved off the calling thread.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedSave {
+    pub(crate) value: Value,
+    pub(crate) human_readable: bool,
+}
+
+impl OwnedSave {
+    /// Returns the underlying [`Value`].
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Returns the underlying [`Value`].
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Returns true if this save should be serialized by a human readable format.
+    pub fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+}
+
+impl Serialize for OwnedSave {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() != self.human_readable {
+            return Err(serde::ser::Error::custom(if self.human_readable {
+                "OwnedSave of a human readable format cannot be serialized by a non-human readable format."
+            } else {
+                "OwnedSave of a non-human readable format cannot be serialized by a human readable format."
+            }));
+        }
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i8<E: de::Error>(self, v: i8) -> Result<Value, E> {
+        Ok(Value::I8(v))
+    }
+
+    fn visit_i16<E: de::Error>(self, v: i16) -> Result<Value, E> {
+        Ok(Value::I16(v))
+    }
+
+    fn visit_i32<E: de::Error>(self, v: i32) -> Result<Value, E> {
+        Ok(Value::I32(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::I64(v))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<Value, E> {
+        Ok(Value::I128(v))
+    }
+
+    fn visit_u8<E: de::Error>(self, v: u8) -> Result<Value, E> {
+        Ok(Value::U8(v))
+    }
+
+    fn visit_u16<E: de::Error>(self, v: u16) -> Result<Value, E> {
+        Ok(Value::U16(v))
+    }
+
+    fn visit_u32<E: de::Error>(self, v: u32) -> Result<Value, E> {
+        Ok(Value::U32(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::U64(v))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Value, E> {
+        Ok(Value::U128(v))
+    }
+
+    fn visit_f32<E: de::Error>(self, v: f32) -> Result<Value, E> {
+        Ok(Value::F32(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::F64(v))
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<Value, E> {
+        Ok(Value::Char(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(v.to_owned()))
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Bytes(v))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Ok(Value::Some(Box::new(Value::deserialize(deserializer)?)))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Value::Seq(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Value::Map(entries))
+    }
+}
+
+/// A [`Deserializer`] that reads from a [`Value`].
+#[derive(Debug, Clone)]
+pub struct ValueDeserializer {
+    value: Value,
+    human_readable: bool,
+}
+
+impl ValueDeserializer {
+    /// Create a [`ValueDeserializer`],
+    /// `human_readable` is the value of [`Deserializer::is_human_readable`].
+    pub fn new(value: Value, human_readable: bool) -> Self {
+        ValueDeserializer {
+            value,
+            human_readable,
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn struct_entries(fields: Vec<(&'static str, Option<Value>)>) -> Vec<(Value, Value)> {
+    fields
+        .into_iter()
+        .filter_map(|(k, v)| Some((Value::String(k.to_owned()), v?)))
+        .collect()
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let human_readable = self.human_readable;
+        match self.value {
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::I8(v) => visitor.visit_i8(v),
+            Value::I16(v) => visitor.visit_i16(v),
+            Value::I32(v) => visitor.visit_i32(v),
+            Value::I64(v) => visitor.visit_i64(v),
+            Value::I128(v) => visitor.visit_i128(v),
+            Value::U8(v) => visitor.visit_u8(v),
+            Value::U16(v) => visitor.visit_u16(v),
+            Value::U32(v) => visitor.visit_u32(v),
+            Value::U64(v) => visitor.visit_u64(v),
+            Value::U128(v) => visitor.visit_u128(v),
+            Value::F32(v) => visitor.visit_f32(v),
+            Value::F64(v) => visitor.visit_f64(v),
+            Value::Char(v) => visitor.visit_char(v),
+            Value::String(v) => visitor.visit_string(v),
+            Value::Bytes(v) => visitor.visit_byte_buf(v),
+            Value::None => visitor.visit_none(),
+            Value::Some(v) => visitor.visit_some(ValueDeserializer::new(*v, human_readable)),
+            Value::Unit | Value::UnitStruct(_) => visitor.visit_unit(),
+            Value::NewtypeStruct(_, v) => {
+                visitor.visit_newtype_struct(ValueDeserializer::new(*v, human_readable))
+            }
+            Value::Seq(items) | Value::Tuple(items) | Value::TupleStruct(_, items) => {
+                visitor.visit_seq(SeqDeserializer::new(items, human_readable))
+            }
+            Value::Map(entries) => visitor.visit_map(MapDeserializer::new(entries, human_readable)),
+            Value::Struct(_, fields) => {
+                visitor.visit_map(MapDeserializer::new(struct_entries(fields), human_readable))
+            }
+            value @ (Value::UnitVariant { .. }
+            | Value::NewtypeVariant { .. }
+            | Value::TupleVariant { .. }
+            | Value::StructVariant { .. }) => {
+                visitor.visit_enum(EnumDeserializer::new(value, human_readable)?)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::None | Value::Unit => visitor.visit_none(),
+            Value::Some(v) => visitor.visit_some(ValueDeserializer::new(*v, self.human_readable)),
+            value => visitor.visit_some(ValueDeserializer::new(value, self.human_readable)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Unit | Value::UnitStruct(_) => visitor.visit_unit(),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value {
+            Value::NewtypeStruct(_, v) => {
+                visitor.visit_newtype_struct(ValueDeserializer::new(*v, self.human_readable))
+            }
+            value => {
+                visitor.visit_newtype_struct(ValueDeserializer::new(value, self.human_readable))
+            }
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(EnumDeserializer::new(self.value, self.human_readable)?)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.human_readable
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqDeserializer {
+    items: std::vec::IntoIter<Value>,
+    human_readable: bool,
+}
+
+impl SeqDeserializer {
+    fn new(items: Vec<Value>, human_readable: bool) -> Self {
+        SeqDeserializer {
+            items: items.into_iter(),
+            human_readable,
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.items.next() {
+            Some(value) => seed
+                .deserialize(ValueDeserializer::new(value, self.human_readable))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+pub(crate) struct MapDeserializer {
+    entries: std::vec::IntoIter<(Value, Value)>,
+    value: Option<Value>,
+    human_readable: bool,
+}
+
+impl MapDeserializer {
+    pub(crate) fn new(entries: Vec<(Value, Value)>, human_readable: bool) -> Self {
+        MapDeserializer {
+            entries: entries.into_iter(),
+            value: None,
+            human_readable,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(ValueDeserializer::new(key, self.human_readable))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(ValueDeserializer::new(value, self.human_readable)),
+            None => Err(de::Error::custom("next_value called before next_key.")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct EnumDeserializer {
+    variant: Value,
+    value: Option<Value>,
+    human_readable: bool,
+}
+
+impl EnumDeserializer {
+    fn new(value: Value, human_readable: bool) -> Result<Self, Error> {
+        let (variant, value) = match value {
+            Value::UnitVariant { variant, .. } => (Value::String(variant.to_owned()), None),
+            Value::NewtypeVariant { variant, value, .. } => {
+                (Value::String(variant.to_owned()), Some(*value))
+            }
+            Value::TupleVariant {
+                variant, fields, ..
+            } => (Value::String(variant.to_owned()), Some(Value::Seq(fields))),
+            Value::StructVariant {
+                variant, fields, ..
+            } => (
+                Value::String(variant.to_owned()),
+                Some(Value::Map(struct_entries(fields))),
+            ),
+            // Externally tagged enums in self-describing formats.
+            Value::String(_) | Value::U32(_) | Value::U64(_) => (value, None),
+            Value::Map(entries) if entries.len() == 1 => {
+                let (variant, value) = entries.into_iter().next().unwrap();
+                (variant, Some(value))
+            }
+            _ => return Err(de::Error::custom("expected an enum.")),
+        };
+        Ok(EnumDeserializer {
+            variant,
+            value,
+            human_readable,
+        })
+    }
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer), Error> {
+        let variant =
+            seed.deserialize(ValueDeserializer::new(self.variant, self.human_readable))?;
+        Ok((
+            variant,
+            VariantDeserializer {
+                value: self.value,
+                human_readable: self.human_readable,
+            },
+        ))
+    }
+}
+
+struct VariantDeserializer {
+    value: Option<Value>,
+    human_readable: bool,
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            None | Some(Value::Unit) => Ok(()),
+            Some(_) => Err(de::Error::custom("expected a unit variant.")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        match self.value {
+            Some(value) => seed.deserialize(ValueDeserializer::new(value, self.human_readable)),
+            None => Err(de::Error::custom("expected a newtype variant.")),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Some(value) => {
+                ValueDeserializer::new(value, self.human_readable).deserialize_any(visitor)
+            }
+            None => Err(de::Error::custom("expected a tuple variant.")),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value {
+            Some(value) => {
+                ValueDeserializer::new(value, self.human_readable).deserialize_any(visitor)
+            }
+            None => Err(de::Error::custom("expected a struct variant.")),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+//! Module for versioned saves and migrations.
+use std::{borrow::Cow, marker::PhantomData, sync::Arc};
+
+use bevy_ecs::{system::Resource, world::World};
+use serde::{
+    de::{DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    value::{Value, ValueDeserializer},
+    BatchSerialization, SerializeLens, WorldExtension,
+};
+
+type MigrationFn = Arc<dyn Fn(&mut Value) -> Result<(), String> + Send + Sync>;
+
+/// A migration of a section from one version to the next.
+#[derive(Clone)]
+pub(crate) struct Migration {
+    section: String,
+    from_version: u32,
+    f: MigrationFn,
+}
+
+/// Registered migrations of versioned saves.
+#[derive(Resource, Default, Clone)]
+pub(crate) struct Migrations(Vec<Migration>);
+
+impl Migrations {
+    pub(crate) fn register(
+        &mut self,
+        section: &str,
+        from_version: u32,
+        f: impl Fn(&mut Value) -> Result<(), String> + Send + Sync + 'static,
+    ) {
+        self.0.push(Migration {
+            section: section.to_owned(),
+            from_version,
+            f: Arc::new(f),
+        });
+        // Stable, migrations of the same version run in registration order.
+        self.0.sort_by_key(|m| m.from_version);
+    }
+
+    /// Returns migrations needed to upgrade from `version` to `current`, in order.
+    fn between(&self, version: u32, current: u32) -> impl Iterator<Item = &Migration> {
+        self.0
+            .iter()
+            .filter(move |m| m.from_version >= version && m.from_version < current)
+    }
+}
+
+const FIELDS: &[&str] = &["version", "data"];
+
+/// Serialize a [`BatchSerialization`] type with a version number.
+pub(crate) struct SerializeVersioned<'t, T: BatchSerialization> {
+    pub(crate) version: u32,
+    pub(crate) lens: SerializeLens<'t, T>,
+}
+
+impl<T: BatchSerialization> Serialize for SerializeVersioned<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut st = serializer.serialize_struct("Versioned", 2)?;
+        st.serialize_field("version", &self.version)?;
+        st.serialize_field("data", &self.lens)?;
+        st.end()
+    }
+}
+
+/// Deserialize a [`BatchSerialization`] type with a version number and run migrations.
+pub(crate) struct DeserializeVersioned<T: BatchSerialization> {
+    pub(crate) current: u32,
+    pub(crate) migrations: Migrations,
+    pub(crate) p: PhantomData<T>,
+}
+
+impl<T: BatchSerialization> DeserializeVersioned<T> {
+    pub(crate) fn new(world: &World, current: u32) -> Self {
+        DeserializeVersioned {
+            current,
+            migrations: world
+                .get_resource::<Migrations>()
+                .cloned()
+                .unwrap_or_default(),
+            p: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: BatchSerialization> DeserializeSeed<'de> for DeserializeVersioned<T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        let human_readable = deserializer.is_human_readable();
+        deserializer.deserialize_struct(
+            "Versioned",
+            FIELDS,
+            VersionedVisitor::<T> {
+                current: self.current,
+                migrations: self.migrations,
+                human_readable,
+                p: PhantomData,
+            },
+        )
+    }
+}
+
+struct VersionedVisitor<T> {
+    current: u32,
+    migrations: Migrations,
+    human_readable: bool,
+    p: PhantomData<T>,
+}
+
+/// Deserialize data of a version.
+struct VersionedData<'t, T> {
+    version: u32,
+    current: u32,
+    migrations: &'t Migrations,
+    human_readable: bool,
+    p: PhantomData<T>,
+}
+
+impl<'de, T: BatchSerialization> DeserializeSeed<'de> for VersionedData<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        if self.version > self.current {
+            return Err(serde::de::Error::custom(format!(
+                "Save version {} is newer than the current version {}.",
+                self.version, self.current
+            )));
+        }
+        let mut migrations = self
+            .migrations
+            .between(self.version, self.current)
+            .peekable();
+        if migrations.peek().is_none() {
+            T::De::deserialize(deserializer)?;
+            return Ok(());
+        }
+        let mut value = Value::deserialize(deserializer)?;
+        let single = if T::LEN == 1 {
+            T::names().first().copied()
+        } else {
+            None
+        };
+        for migration in migrations {
+            let section = if single == Some(migration.section.as_str()) {
+                Some(&mut value)
+            } else if single.is_none() {
+                match &mut value {
+                    // `Positional` batches.
+                    Value::Seq(items) | Value::Tuple(items) => T::names()
+                        .iter()
+                        .position(|x| *x == migration.section)
+                        .and_then(|i| items.get_mut(i)),
+                    value => value.get_mut(&migration.section),
+                }
+            } else {
+                None
+            };
+            if let Some(section) = section {
+                (migration.f)(section).map_err(|e| {
+                    serde::de::Error::custom(format!(
+                        "Migration of section {} from version {} failed: {e}",
+                        migration.section, migration.from_version
+                    ))
+                })?;
+            }
+        }
+        T::De::deserialize(ValueDeserializer::new(value, self.human_readable))
+            .map_err(serde::de::Error::custom)?;
+        Ok(())
+    }
+}
+
+impl<'de, T: BatchSerialization> Visitor<'de> for VersionedVisitor<T> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a versioned save")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let Some(version) = seq.next_element::<u32>()? else {
+            return Err(serde::de::Error::invalid_length(0, &self));
+        };
+        let data = VersionedData::<T> {
+            version,
+            current: self.current,
+            migrations: &self.migrations,
+            human_readable: self.human_readable,
+            p: PhantomData,
+        };
+        if seq.next_element_seed(data)?.is_none() {
+            return Err(serde::de::Error::invalid_length(1, &self));
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let mut version = None;
+        let mut loaded = false;
+        // `data` before `version` is buffered until the version is known.
+        let mut buffered = None;
+        while let Some(key) = map.next_key::<Cow<str>>()? {
+            match key.as_ref() {
+                "version" => version = Some(map.next_value::<u32>()?),
+                "data" if loaded || buffered.is_some() => {
+                    return Err(serde::de::Error::duplicate_field("data"))
+                }
+                "data" => match version {
+                    Some(version) => {
+                        map.next_value_seed(VersionedData::<T> {
+                            version,
+                            current: self.current,
+                            migrations: &self.migrations,
+                            human_readable: self.human_readable,
+                            p: PhantomData,
+                        })?;
+                        loaded = true;
+                    }
+                    None => buffered = Some(map.next_value::<Value>()?),
+                },
+                field => return Err(serde::de::Error::unknown_field(field, FIELDS)),
+            }
+        }
+        let Some(version) = version else {
+            return Err(serde::de::Error::missing_field("version"));
+        };
+        if let Some(value) = buffered {
+            VersionedData::<T> {
+                version,
+                current: self.current,
+                migrations: &self.migrations,
+                human_readable: self.human_readable,
+                p: PhantomData,
+            }
+            .deserialize(ValueDeserializer::new(value, self.human_readable))
+            .map_err(serde::de::Error::custom)?;
+        } else if !loaded {
+            return Err(serde::de::Error::missing_field("data"));
+        }
+        Ok(())
+    }
+}
+
+/// Create a [`SerializeVersioned`] from a [`World`].
+pub(crate) fn serialize_versioned<T: BatchSerialization>(
+    world: &mut World,
+    version: u32,
+) -> SerializeVersioned<'_, T> {
+    SerializeVersioned {
+        version,
+        lens: world.serialize_lens::<T>(),
+    }
+}
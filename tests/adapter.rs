@@ -0,0 +1,90 @@
+use bevy_ecs::{component::Component, world::World};
+use bevy_serde_lens::{Adapted, BevyObject, SerializeAs, WorldExtension};
+use serde_json::json;
+
+/// A component without serde support.
+#[derive(Component, Debug, PartialEq)]
+pub struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+#[derive(Component, Debug, PartialEq)]
+pub struct Size(f32, f32);
+
+pub struct ColorAdapter;
+
+impl SerializeAs<Color> for ColorAdapter {
+    type Proxy = String;
+
+    fn to_proxy(value: &Color) -> String {
+        format!("#{:02x}{:02x}{:02x}", value.r, value.g, value.b)
+    }
+
+    fn from_proxy(proxy: String) -> Color {
+        let channel = |i: usize| u8::from_str_radix(&proxy[i..i + 2], 16).unwrap_or_default();
+        Color {
+            r: channel(1),
+            g: channel(3),
+            b: channel(5),
+        }
+    }
+}
+
+pub struct SizeAdapter;
+
+impl SerializeAs<Size> for SizeAdapter {
+    type Proxy = [f32; 2];
+
+    fn to_proxy(value: &Size) -> [f32; 2] {
+        [value.0, value.1]
+    }
+
+    fn from_proxy(proxy: [f32; 2]) -> Size {
+        Size(proxy[0], proxy[1])
+    }
+}
+
+#[derive(BevyObject)]
+pub struct SerializeSprite {
+    #[bevy_object(adapter = "ColorAdapter")]
+    color: Color,
+    size: Adapted<Size, SizeAdapter>,
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.spawn((
+        Color {
+            r: 255,
+            g: 128,
+            b: 0,
+        },
+        Size(1.0, 2.0),
+    ));
+    world.spawn(Color { r: 0, g: 0, b: 0 });
+    let value = serde_json::to_value(world.serialize_lens::<SerializeSprite>()).unwrap();
+    assert_eq!(value, json!([{"color": "#ff8000", "size": [1.0, 2.0]}]));
+
+    world.despawn_bound_objects::<SerializeSprite>();
+    assert_eq!(world.entities().len(), 1);
+    world.load::<SerializeSprite, _>(&value).unwrap();
+    let (color, size) = world.query::<(&Color, &Size)>().single(&world);
+    assert_eq!(
+        color,
+        &Color {
+            r: 255,
+            g: 128,
+            b: 0
+        }
+    );
+    assert_eq!(size, &Size(1.0, 2.0));
+
+    world.despawn_bound_objects::<SerializeSprite>();
+    let err = world
+        .load::<SerializeSprite, _>(&json!([{"color": "#ffffff", "size": "large"}]))
+        .unwrap_err();
+    assert_eq!(err.path.to_string(), "SerializeSprite[0].size.Size");
+}
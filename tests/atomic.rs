@@ -0,0 +1,71 @@
+use bevy_ecs::{component::Component, system::Resource, world::World};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{batch, SerializeNonSend, SerializeResource, WorldExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct A(u32);
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct B(u32);
+
+#[derive(Debug, Serialize, Deserialize, Resource, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct R(u32);
+
+#[derive(Debug, Serialize, Deserialize, Resource, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct S(u32);
+
+#[derive(Debug, Serialize, Deserialize, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct N(u32);
+
+type Batch = batch!(
+    A,
+    SerializeResource<R>,
+    SerializeResource<S>,
+    SerializeNonSend<N>,
+    B
+);
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.spawn(A(0));
+    world.insert_resource(R(1));
+    world.insert_non_send_resource(N(2));
+
+    assert!(world
+        .load::<Batch, _>(json!({
+            "A": [1, 2, 3],
+            "R": 4,
+            "S": 5,
+            "N": 6,
+            "B": [7, "bad"],
+        }))
+        .is_err());
+
+    assert_eq!(world.entities().len(), 1);
+    assert_eq!(world.resource::<R>(), &R(1));
+    assert!(!world.contains_resource::<S>());
+    assert_eq!(world.non_send_resource::<N>(), &N(2));
+
+    world
+        .load::<Batch, _>(json!({
+            "A": [1, 2, 3],
+            "R": 4,
+            "S": 5,
+            "N": 6,
+            "B": [7, 8],
+        }))
+        .unwrap();
+
+    assert_eq!(world.entities().len(), 6);
+    assert_eq!(world.resource::<R>(), &R(4));
+    assert_eq!(world.resource::<S>(), &S(5));
+    assert_eq!(world.non_send_resource::<N>(), &N(6));
+}
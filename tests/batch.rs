@@ -233,3 +233,22 @@ pub fn test() {
         ])
     );
 }
+
+type ABC = batch!(A, B, C);
+
+#[test]
+pub fn test_sections_once() {
+    let mut world = World::new();
+    world.spawn(A('a'));
+    world.spawn(B(1.0));
+    world.spawn(C("Crab".to_owned()));
+
+    let mut vec = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut vec);
+    world.save::<ABC, _>(&mut serializer).unwrap();
+    let json = String::from_utf8(vec).unwrap();
+
+    for name in ["\"A\"", "\"B\"", "\"C\""] {
+        assert_eq!(json.matches(name).count(), 1, "{name} in {json}");
+    }
+}
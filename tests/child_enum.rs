@@ -0,0 +1,95 @@
+use bevy_ecs::{component::Component, world::World};
+use bevy_hierarchy::{BuildWorldChildren, Children};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{BevyObject, ChildVec, WorldExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Panel(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Button(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Label(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct FontSize(u32);
+
+#[derive(BevyObject)]
+pub struct SerializeLabel {
+    label: Label,
+    size: FontSize,
+}
+
+#[derive(BevyObject)]
+#[serde(rename_all = "snake_case")]
+pub enum Widget {
+    Button(Button),
+    Label(SerializeLabel),
+}
+
+#[derive(BevyObject)]
+pub struct SerializePanel {
+    panel: Panel,
+    widgets: ChildVec<Widget>,
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world
+        .spawn(Panel("Menu".to_owned()))
+        .with_children(|builder| {
+            builder.spawn(Button("Start".to_owned()));
+            builder.spawn((Label("Title".to_owned()), FontSize(24)));
+            builder.spawn(Button("Quit".to_owned()));
+            builder.spawn(FontSize(12));
+        });
+    let value = serde_json::to_value(world.serialize_lens::<SerializePanel>()).unwrap();
+    assert_eq!(
+        value,
+        json!([{
+            "panel": "Menu",
+            "widgets": [
+                {"button": "Start"},
+                {"label": {"label": "Title", "size": 24}},
+                {"button": "Quit"},
+            ],
+        }])
+    );
+
+    world.despawn_bound_objects::<SerializePanel>();
+    assert_eq!(world.entities().len(), 0);
+    world.load::<SerializePanel, _>(&value).unwrap();
+    let value2 = serde_json::to_value(world.serialize_lens::<SerializePanel>()).unwrap();
+    assert_eq!(value, value2);
+    let (_, children) = world.query::<(&Panel, &Children)>().single(&world);
+    let children = children.to_vec();
+    assert_eq!(world.get::<Button>(children[0]).unwrap().0, "Start");
+    assert_eq!(world.get::<Label>(children[1]).unwrap().0, "Title");
+    assert_eq!(world.get::<Button>(children[2]).unwrap().0, "Quit");
+
+    let value = serde_json::to_value(world.serialize_lens::<Widget>()).unwrap();
+    assert_eq!(value.as_array().unwrap().len(), 3);
+
+    world.despawn_bound_objects::<SerializePanel>();
+    let err = world
+        .load::<SerializePanel, _>(&json!([{
+            "panel": "Menu",
+            "widgets": [
+                {"button": "Start"},
+                {"label": {"label": "Title", "size": "large"}},
+            ],
+        }]))
+        .unwrap_err();
+    assert_eq!(
+        err.path.to_string(),
+        "SerializePanel[0].widgets[1].Label.size.FontSize"
+    );
+}
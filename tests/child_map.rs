@@ -0,0 +1,96 @@
+use bevy_ecs::{component::Component, world::World};
+use bevy_hierarchy::{BuildWorldChildren, Children};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{BevyObject, ChildMap, WorldExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Slot(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Item(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Durability(u32);
+
+#[derive(BevyObject)]
+pub struct SerializeItem {
+    item: Item,
+    durability: Durability,
+}
+
+#[derive(BevyObject)]
+pub struct SerializeUnit {
+    unit: Unit,
+    items: ChildMap<Slot, SerializeItem>,
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world
+        .spawn(Unit("Alice".to_owned()))
+        .with_children(|builder| {
+            builder.spawn((
+                Slot("head".to_owned()),
+                Item("Helmet".to_owned()),
+                Durability(3),
+            ));
+            builder.spawn((
+                Slot("hand".to_owned()),
+                Item("Sword".to_owned()),
+                Durability(5),
+            ));
+            builder.spawn(Item("Not an item".to_owned()));
+        });
+    let value = serde_json::to_value(world.serialize_lens::<SerializeUnit>()).unwrap();
+    assert_eq!(
+        value,
+        json!([{
+            "unit": "Alice",
+            "items": {
+                "head": {"item": "Helmet", "durability": 3},
+                "hand": {"item": "Sword", "durability": 5},
+            },
+        }])
+    );
+
+    world.despawn_bound_objects::<SerializeUnit>();
+    assert_eq!(world.entities().len(), 0);
+    world.load::<SerializeUnit, _>(&value).unwrap();
+    assert_eq!(world.entities().len(), 3);
+    let value2 = serde_json::to_value(world.serialize_lens::<SerializeUnit>()).unwrap();
+    assert_eq!(value, value2);
+    let (unit, children) = world.query::<(&Unit, &Children)>().single(&world);
+    assert_eq!(unit.0, "Alice");
+    assert_eq!(
+        world.get::<Slot>(children[1]).map(|x| x.0.as_str()),
+        Some("hand")
+    );
+
+    world.despawn_bound_objects::<SerializeUnit>();
+    let err = world
+        .load::<SerializeUnit, _>(&mut serde_json::Deserializer::from_str(
+            r#"[{"unit": "Bob", "items": {
+                "head": {"item": "Helmet", "durability": 3},
+                "head": {"item": "Hat", "durability": 1}
+            }}]"#,
+        ))
+        .unwrap_err();
+    assert_eq!(err.path.to_string(), "SerializeUnit[0].items[1]");
+    assert!(err.to_string().contains("Duplicate key"));
+    assert_eq!(world.entities().len(), 0);
+
+    let alice = world.spawn(Unit("Alice".to_owned())).id();
+    let sword = world.spawn((Item("Sword".to_owned()), Durability(5))).id();
+    world.entity_mut(alice).add_child(sword);
+    assert!(serde_json::to_value(world.serialize_lens::<SerializeUnit>()).is_err());
+}
@@ -0,0 +1,95 @@
+use bevy_ecs::{component::Component, world::World};
+use bevy_hierarchy::{BuildWorldChildren, Children, Parent};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{BevyObject, ChildTree, WorldExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Scene(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Node(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Other(String);
+
+#[derive(BevyObject)]
+pub struct SerializeScene {
+    scene: Scene,
+    nodes: ChildTree<Node>,
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world
+        .spawn(Scene("Level".to_owned()))
+        .with_children(|builder| {
+            builder
+                .spawn(Node("body".to_owned()))
+                .with_children(|builder| {
+                    builder
+                        .spawn(Node("arm".to_owned()))
+                        .with_children(|builder| {
+                            builder.spawn(Node("hand".to_owned()));
+                        });
+                    builder.spawn(Node("leg".to_owned()));
+                    builder
+                        .spawn(Other("not a node".to_owned()))
+                        .with_children(|builder| {
+                            builder.spawn(Node("hidden".to_owned()));
+                        });
+                });
+            builder.spawn(Node("camera".to_owned()));
+        });
+    let value = serde_json::to_value(world.serialize_lens::<SerializeScene>()).unwrap();
+    assert_eq!(
+        value,
+        json!([{
+            "scene": "Level",
+            "nodes": [
+                ["body", [
+                    ["arm", [["hand", []]]],
+                    ["leg", []],
+                ]],
+                ["camera", []],
+            ],
+        }])
+    );
+
+    world.despawn_bound_objects::<SerializeScene>();
+    assert_eq!(world.entities().len(), 0);
+    world.load::<SerializeScene, _>(&value).unwrap();
+    assert_eq!(world.entities().len(), 6);
+    let value2 = serde_json::to_value(world.serialize_lens::<SerializeScene>()).unwrap();
+    assert_eq!(value, value2);
+
+    let (arm, body) = world
+        .query::<(&Node, &Parent)>()
+        .iter(&world)
+        .find(|(node, _)| node.0 == "hand")
+        .map(|(_, parent)| {
+            (
+                parent.get(),
+                world.get::<Parent>(parent.get()).unwrap().get(),
+            )
+        })
+        .unwrap();
+    assert_eq!(world.get::<Node>(arm).unwrap().0, "arm");
+    assert_eq!(world.get::<Node>(body).unwrap().0, "body");
+    let (_, children) = world.query::<(&Scene, &Children)>().single(&world);
+    assert_eq!(children.len(), 2);
+
+    world.despawn_bound_objects::<SerializeScene>();
+    let err = world
+        .load::<SerializeScene, _>(&json!([{
+            "scene": "Level",
+            "nodes": [["body", [["arm", 1]]]],
+        }]))
+        .unwrap_err();
+    assert_eq!(err.path.to_string(), "SerializeScene[0].nodes[0][0]");
+}
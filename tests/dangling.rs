@@ -0,0 +1,80 @@
+use bevy_ecs::{component::Component, entity::Entity, world::World};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    entity::{EntityPtr, OptionEntityPtr},
+    BevyObject, DanglingPolicy, EntityId, EntityRefMode, Maybe, WorldExtension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Target(#[serde(with = "EntityPtr")] Entity);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Follow(#[serde(with = "OptionEntityPtr")] Option<Entity>);
+
+#[derive(BevyObject)]
+pub struct SerializeUnit {
+    id: EntityId,
+    unit: Unit,
+    follow: Follow,
+    #[serde(default)]
+    target: Maybe<Target>,
+}
+
+fn save(world: &mut World) -> Result<serde_json::Value, serde_json::Error> {
+    serde_json::to_value(world.serialize_lens::<SerializeUnit>())
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    let alice = world.spawn((Unit("Alice".to_owned()), Follow(None))).id();
+    let outside = world.spawn_empty().id();
+    let bob = world
+        .spawn((Unit("Bob".to_owned()), Follow(Some(alice)), Target(alice)))
+        .id();
+    save(&mut world).unwrap();
+
+    world.entity_mut(alice).insert(Follow(Some(bob)));
+    let err = save(&mut world).unwrap_err();
+    assert!(err.to_string().ends_with("is not part of the save."));
+
+    world.insert_resource(EntityRefMode::Deferred);
+    save(&mut world).unwrap();
+
+    world
+        .entity_mut(bob)
+        .insert((Follow(Some(outside)), Target(outside)));
+    assert!(save(&mut world).is_err());
+
+    world.insert_resource(DanglingPolicy::Null);
+    assert!(save(&mut world).is_err());
+    world.entity_mut(bob).remove::<Target>();
+    let value = save(&mut world).unwrap();
+    assert_eq!(value[1]["follow"], json!(null));
+    assert_eq!(value[1]["target"], json!(null));
+
+    world.insert_resource(DanglingPolicy::DropComponent);
+    world.entity_mut(bob).insert(Target(outside));
+    assert!(save(&mut world).is_err());
+    world.entity_mut(bob).insert(Follow(Some(alice)));
+    let value = save(&mut world).unwrap();
+    assert_eq!(value[1]["follow"], value[0]["id"]);
+    assert_eq!(value[1]["target"], json!(null));
+    world.entity_mut(bob).insert(Target(alice));
+    let value = save(&mut world).unwrap();
+    assert_eq!(value[1]["target"], value[0]["id"]);
+
+    // Buffered objects produce the same output in non-self-describing formats.
+    let bytes = postcard::to_allocvec(&world.serialize_lens::<SerializeUnit>()).unwrap();
+    world.insert_resource(DanglingPolicy::Error);
+    let bytes2 = postcard::to_allocvec(&world.serialize_lens::<SerializeUnit>()).unwrap();
+    assert_eq!(bytes, bytes2);
+}
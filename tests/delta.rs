@@ -0,0 +1,152 @@
+use bevy_ecs::{
+    component::{Component, Tick},
+    world::World,
+};
+use bevy_hierarchy::{BuildWorldChildren, DespawnRecursiveExt};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{BevyObject, ChildVec, Maybe, WorldExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq, Eq, Hash, Clone)]
+#[serde(transparent)]
+pub struct Id(u32);
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Hp(u32);
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Shield(u32);
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Potion(String);
+
+#[derive(BevyObject)]
+pub struct Unit {
+    id: Id,
+    hp: Hp,
+}
+
+#[derive(BevyObject)]
+pub struct Squad {
+    id: Id,
+    #[serde(default)]
+    shield: Maybe<Shield>,
+    #[serde(default)]
+    potions: ChildVec<Potion>,
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.spawn((Id(0), Hp(10)));
+    let b = world.spawn((Id(1), Hp(20))).id();
+    let c = world.spawn((Id(2), Hp(30))).id();
+
+    let tick = world.change_tick();
+    let value = world
+        .save_delta::<Unit, Id, _>(tick, serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!(value, json!({"changed": [], "removed": []}));
+
+    world.increment_change_tick();
+    world.get_mut::<Hp>(b).unwrap().0 = 25;
+    world.despawn(c);
+    world.spawn((Id(3), Hp(40)));
+
+    let value = world
+        .save_delta::<Unit, Id, _>(tick, serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "changed": [
+                {"id": 1, "hp": 25},
+                {"id": 3, "hp": 40},
+            ],
+            "removed": [2]
+        })
+    );
+
+    let mut other = World::new();
+    other.spawn((Id(0), Hp(10)));
+    other.spawn((Id(1), Hp(20)));
+    other.spawn((Id(2), Hp(30)));
+    other.apply_delta::<Unit, Id, _>(value).unwrap();
+
+    let mut query = other.query::<(&Id, &Hp)>();
+    let mut result: Vec<_> = query.iter(&other).map(|(id, hp)| (id.0, hp.0)).collect();
+    result.sort();
+    assert_eq!(result, vec![(0, 10), (1, 25), (3, 40)]);
+    assert_eq!(other.entities().len(), 3);
+
+    let tick = world.change_tick();
+    world.increment_change_tick();
+    let value = world
+        .save_delta::<Unit, Id, _>(tick, serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!(value, json!({"changed": [], "removed": []}));
+}
+
+#[test]
+pub fn test_removals() {
+    let mut world = World::new();
+    world.track_delta::<Squad, Id>();
+    let a = world.spawn((Id(0), Shield(5))).id();
+    let b = world
+        .spawn(Id(1))
+        .with_children(|b| {
+            b.spawn(Potion("Hp".to_owned()));
+        })
+        .id();
+    let c = world.spawn(Id(2)).id();
+    let d = world.spawn(Id(3)).id();
+    // Objects without a key are not part of the delta.
+    world.spawn(Shield(1));
+
+    let tick = world.change_tick();
+    world.increment_change_tick();
+    // Despawned before the first delta.
+    world.despawn(d);
+    world.entity_mut(a).remove::<Shield>();
+    let potion = world.get::<bevy_hierarchy::Children>(b).unwrap()[0];
+    world.entity_mut(c).add_child(potion);
+
+    let value = world
+        .save_delta::<Squad, Id, _>(tick, serde_json::value::Serializer)
+        .unwrap();
+    let mut changed = value["changed"].as_array().unwrap().clone();
+    changed.sort_by_key(|x| x["id"].as_u64());
+    assert_eq!(
+        changed,
+        vec![
+            json!({"id": 0, "shield": null, "potions": []}),
+            json!({"id": 1, "shield": null, "potions": []}),
+            json!({"id": 2, "shield": null, "potions": ["Hp"]}),
+        ]
+    );
+    assert_eq!(value["removed"], json!([3]));
+
+    // Tombstones are relative to `since`, not to the last call.
+    let value2 = world
+        .save_delta::<Squad, Id, _>(tick, serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!(value2["removed"], json!([3]));
+
+    let tick = world.change_tick();
+    world.increment_change_tick();
+    world.entity_mut(c).despawn_recursive();
+    let value = world
+        .save_delta::<Squad, Id, _>(tick, serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!(value, json!({"changed": [], "removed": [2]}));
+
+    // Despawns before the last `since` are discarded.
+    let value = world
+        .save_delta::<Squad, Id, _>(Tick::new(0), serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!(value["removed"], json!([2]));
+}
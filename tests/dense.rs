@@ -0,0 +1,173 @@
+use bevy_ecs::{
+    component::Component,
+    entity::{Entity, EntityMapper, MapEntities},
+    world::World,
+};
+use bevy_hierarchy::{BuildWorldChildren, Parent};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    batch, entity::EntityPtr, BevyObject, EntityId, EntityNumbering, EntityRefMode, EntityResolver,
+    Parented, WorldExtension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Target(#[serde(with = "EntityPtr")] Entity);
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeUnit {
+    id: EntityId,
+    unit: Unit,
+    target: Target,
+}
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeItem {
+    item: Unit,
+    parent: Parented,
+}
+
+type SaveFile = batch!(SerializeUnit, SerializeItem);
+
+fn spawn(world: &mut World) {
+    let alice = world.spawn(Unit("Alice".to_owned())).id();
+    let bob = world.spawn((Unit("Bob".to_owned()), Target(alice))).id();
+    world.entity_mut(alice).insert(Target(bob));
+    world.spawn(Unit("Sword".to_owned())).set_parent(bob);
+}
+
+fn find(world: &mut World, name: &str) -> Entity {
+    world
+        .query::<(Entity, &Unit)>()
+        .iter(world)
+        .find(|(_, x)| x.0 == name)
+        .unwrap()
+        .0
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.insert_resource(EntityNumbering::Dense);
+    world.insert_resource(EntityRefMode::Deferred);
+    world.spawn_empty();
+    spawn(&mut world);
+    let value = serde_json::to_value(world.serialize_lens::<SaveFile>()).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "SerializeUnit": [
+                {"id": 0, "unit": "Alice", "target": 1},
+                {"id": 1, "unit": "Bob", "target": 0},
+            ],
+            "SerializeItem": [
+                {"item": "Sword", "parent": 1},
+            ],
+        })
+    );
+
+    // Identical worlds produce identical saves.
+    let mut other = World::new();
+    other.insert_resource(EntityNumbering::Dense);
+    other.insert_resource(EntityRefMode::Deferred);
+    spawn(&mut other);
+    let sword = find(&mut other, "Sword");
+    other.despawn(sword);
+    let bob = find(&mut other, "Bob");
+    other.spawn(Unit("Sword".to_owned())).set_parent(bob);
+    let other = serde_json::to_value(other.serialize_lens::<SaveFile>()).unwrap();
+    assert_eq!(value, other);
+
+    let mut world = World::new();
+    world.insert_resource(EntityNumbering::Dense);
+    world.insert_resource(EntityRefMode::Deferred);
+    let report = world.load::<SaveFile, _>(&value).unwrap();
+    let alice = find(&mut world, "Alice");
+    let bob = find(&mut world, "Bob");
+    let sword = find(&mut world, "Sword");
+    assert_eq!(report.entity(0), Some(alice));
+    assert_eq!(report.entity(1), Some(bob));
+    assert_eq!(world.get::<Target>(alice).unwrap().0, bob);
+    assert_eq!(world.get::<Target>(bob).unwrap().0, alice);
+    assert_eq!(world.get::<Parent>(sword).unwrap().get(), bob);
+
+    let err = world
+        .load::<SaveFile, _>(&json!({
+            "SerializeUnit": [{"id": 0, "unit": "Carol", "target": 5}],
+        }))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Entity 5 not serialized.");
+    assert_eq!(world.entities().len(), 3);
+}
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+pub struct Player;
+
+pub struct PlayerResolver;
+
+impl EntityResolver for PlayerResolver {
+    fn save(&self, world: &World, entity: Entity) -> Option<u64> {
+        world.get::<Player>(entity).map(|_| 0)
+    }
+
+    fn load(&self, world: &mut World, _: u64) -> Option<Entity> {
+        world
+            .query_filtered::<Entity, bevy_ecs::query::With<Player>>()
+            .iter(world)
+            .next()
+    }
+}
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Ally(Entity);
+
+impl MapEntities for Ally {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.0 = entity_mapper.map_entity(self.0);
+    }
+}
+
+#[derive(BevyObject)]
+pub struct SerializeAlly {
+    id: EntityId,
+    ally: Ally,
+}
+
+#[test]
+pub fn test_unsupported() {
+    let mut world = World::new();
+    world.insert_resource(EntityNumbering::Dense);
+    world.register_entity_resolver(PlayerResolver);
+    let player = world.spawn(Player).id();
+    world.spawn((Unit("Alice".to_owned()), Target(player)));
+    world.spawn((Unit("Bob".to_owned()), Target(player)));
+
+    // Dense ids and resolved ids share the same space.
+    let err = serde_json::to_value(world.serialize_lens::<SerializeUnit>()).unwrap_err();
+    assert!(err.to_string().contains("EntityNumbering::Dense"));
+
+    // Resolvers are not used on load.
+    assert!(world
+        .load::<SerializeUnit, _>(&json!([{"id": 1, "unit": "Carol", "target": 0}]))
+        .is_err());
+
+    world.register_map_entities::<Ally>();
+    world.spawn(Ally(player));
+    // Entities of mapped components would be saved as bits.
+    let err = serde_json::to_value(world.serialize_lens::<SerializeAlly>()).unwrap_err();
+    assert!(err.to_string().contains("EntityNumbering::Dense"));
+
+    let err = world
+        .load::<SerializeAlly, _>(&json!([{"id": 0, "ally": Entity::from_raw(0).to_bits()}]))
+        .unwrap_err();
+    assert!(err.to_string().contains("EntityNumbering::Dense"));
+}
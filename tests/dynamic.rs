@@ -0,0 +1,128 @@
+use bevy_ecs::{component::Component, system::Resource, world::World};
+use bevy_hierarchy::BuildWorldChildren;
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    BevyObject, ChildVec, DynamicBatch, IncrementalLoad, LoadBudget, Positional, SerializeResource,
+    WorldExtension,
+};
+use postcard::ser_flavors::Flavor;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Potion(String);
+
+#[derive(Serialize, Deserialize, Resource, TypePath)]
+#[serde(transparent)]
+pub struct Gold(u32);
+
+#[derive(BevyObject)]
+pub struct SerializeUnit {
+    unit: Unit,
+    potions: ChildVec<Potion>,
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.insert_resource(
+        DynamicBatch::new()
+            .with::<SerializeUnit>()
+            .with::<SerializeResource<Gold>>(),
+    );
+    assert_eq!(world.resource::<DynamicBatch>().len(), 2);
+    world.insert_resource(Gold(4));
+    world.spawn(Unit("Alice".to_owned()));
+    world.spawn(Unit("Bob".to_owned())).with_children(|b| {
+        b.spawn(Potion("Hp".to_owned()));
+    });
+
+    let value = serde_json::to_value(world.serialize_lens::<DynamicBatch>()).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "SerializeUnit": [
+                {"unit": "Alice", "potions": []},
+                {"unit": "Bob", "potions": ["Hp"]},
+            ],
+            "Gold": 4,
+        })
+    );
+
+    let mut serializer = postcard::Serializer {
+        output: postcard::ser_flavors::AllocVec::new(),
+    };
+    world.save::<DynamicBatch, _>(&mut serializer).unwrap();
+    let bytes = serializer.output.finalize().unwrap();
+
+    world.despawn_bound_objects::<DynamicBatch>();
+    assert_eq!(world.entities().len(), 0);
+    assert!(!world.contains_resource::<Gold>());
+
+    let report = world
+        .load::<DynamicBatch, _>(&mut postcard::Deserializer::from_bytes(&bytes))
+        .unwrap();
+    assert_eq!(report.get("SerializeUnit").len(), 2);
+    assert_eq!(world.entities().len(), 3);
+    assert_eq!(world.resource::<Gold>().0, 4);
+
+    world.load::<DynamicBatch, _>(&value).unwrap();
+    assert_eq!(world.entities().len(), 6);
+
+    world
+        .load_replace::<DynamicBatch, _>(&json!({
+            "SerializeUnit": [{"unit": "Carol", "potions": []}],
+        }))
+        .unwrap();
+    assert_eq!(world.entities().len(), 1);
+    assert!(!world.contains_resource::<Gold>());
+
+    let mut loader = IncrementalLoad::<DynamicBatch>::new(&value).unwrap();
+    while !loader.step(&mut world, LoadBudget::Entities(1)).unwrap() {}
+    assert_eq!(world.entities().len(), 4);
+    assert_eq!(world.resource::<Gold>().0, 4);
+
+    assert!(world
+        .load::<DynamicBatch, _>(&json!({"Silver": 1}))
+        .is_err());
+    world.resource_mut::<DynamicBatch>().remove("SerializeUnit");
+    assert_eq!(
+        serde_json::to_value(world.serialize_lens::<DynamicBatch>()).unwrap(),
+        json!({"Gold": 4})
+    );
+}
+
+#[test]
+pub fn test_positional() {
+    let mut world = World::new();
+    world.insert_resource(
+        DynamicBatch::new()
+            .with::<SerializeUnit>()
+            .with::<SerializeResource<Gold>>(),
+    );
+    world.insert_resource(Gold(4));
+    world.spawn(Unit("Alice".to_owned()));
+
+    let value = serde_json::to_value(world.serialize_lens::<Positional<DynamicBatch>>()).unwrap();
+    assert_eq!(value, json!([[{"unit": "Alice", "potions": []}], 4]));
+
+    let mut serializer = postcard::Serializer {
+        output: postcard::ser_flavors::AllocVec::new(),
+    };
+    world
+        .save::<Positional<DynamicBatch>, _>(&mut serializer)
+        .unwrap();
+    let bytes = serializer.output.finalize().unwrap();
+
+    world.despawn_bound_objects::<DynamicBatch>();
+    world
+        .load::<Positional<DynamicBatch>, _>(&mut postcard::Deserializer::from_bytes(&bytes))
+        .unwrap();
+    assert_eq!(world.entities().len(), 1);
+    assert_eq!(world.resource::<Gold>().0, 4);
+}
@@ -0,0 +1,107 @@
+use bevy_ecs::{component::Component, entity::Entity, world::World};
+use bevy_hierarchy::{Children, Parent};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    batch,
+    entity::{EntityPtr, OptionEntityPtr},
+    BevyObject, ChildVec, EntityId, EntityRefMode, Parented, WorldExtension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Target(#[serde(with = "EntityPtr")] Entity);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Follow(#[serde(with = "OptionEntityPtr")] Option<Entity>);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Potion(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Ring(String);
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeRing {
+    ring: Ring,
+    parent: Parented,
+}
+
+#[derive(BevyObject)]
+pub struct SerializeUnit {
+    target: Target,
+    follow: Follow,
+    id: EntityId,
+    unit: Unit,
+    potions: ChildVec<Potion>,
+}
+
+type SaveFile = batch!(SerializeRing, SerializeUnit);
+
+fn find(world: &mut World, name: &str) -> Entity {
+    world
+        .query::<(Entity, &Unit)>()
+        .iter(world)
+        .find(|(_, x)| x.0 == name)
+        .unwrap()
+        .0
+}
+
+#[test]
+pub fn test() {
+    let value = json!({
+        "SerializeRing": [
+            {"ring": "Gold", "parent": 2},
+        ],
+        "SerializeUnit": [
+            {"target": 2, "follow": 1, "id": 1, "unit": "Alice", "potions": []},
+            {"target": 1, "follow": null, "id": 2, "unit": "Bob", "potions": ["Hp"]},
+        ],
+    });
+
+    let mut world = World::new();
+    assert!(world.load::<SaveFile, _>(&value).is_err());
+    assert_eq!(world.entities().len(), 0);
+
+    world.insert_resource(EntityRefMode::Deferred);
+    let report = world.load::<SaveFile, _>(&value).unwrap();
+    assert_eq!(world.entities().len(), 4);
+
+    let alice = find(&mut world, "Alice");
+    let bob = find(&mut world, "Bob");
+    assert_eq!(report.get("SerializeUnit"), [alice, bob]);
+    assert_eq!(report.entity(1), Some(alice));
+    assert_eq!(report.entity(2), Some(bob));
+    assert_eq!(world.get::<Target>(alice).unwrap().0, bob);
+    assert_eq!(world.get::<Target>(bob).unwrap().0, alice);
+    assert_eq!(world.get::<Follow>(alice).unwrap().0, Some(alice));
+    assert_eq!(world.get::<Follow>(bob).unwrap().0, None);
+
+    let ring = report.get("SerializeRing")[0];
+    assert_eq!(world.get::<Parent>(ring).unwrap().get(), bob);
+    let children = world.get::<Children>(bob).unwrap();
+    assert_eq!(children.len(), 2);
+    assert!(children
+        .iter()
+        .all(|x| world.get::<Parent>(*x).unwrap().get() == bob));
+
+    let err = world
+        .load::<SaveFile, _>(&json!({
+            "SerializeRing": [],
+            "SerializeUnit": [
+                {"target": 1, "follow": 7, "id": 1, "unit": "Carol", "potions": ["Mp"]},
+            ],
+        }))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Entity 7 not serialized.");
+    assert_eq!(world.entities().len(), 4);
+}
@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use bevy_ecs::{component::Component, system::Resource, world::World};
+use bevy_hierarchy::{Children, Parent};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    batch, BevyObject, ChildVec, EntityId, IncrementalLoad, LoadBudget, Parented,
+    SerializeResource, WorldExtension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Potion(String);
+
+#[derive(Debug, Serialize, Deserialize, Resource, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Gold(u32);
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeUnit {
+    entity: EntityId,
+    unit: Unit,
+}
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializePotion {
+    potion: Potion,
+    parent: Parented,
+}
+
+type SaveFile = batch!(SerializeUnit, SerializeResource<Gold>, SerializePotion);
+
+#[test]
+pub fn test() {
+    let json = json!({
+        "SerializeUnit": [
+            {"entity": 0, "unit": "Bob"},
+            {"entity": 1, "unit": "Alice"},
+            {"entity": 2, "unit": "Eric"},
+        ],
+        "Gold": 100,
+        "SerializePotion": [
+            {"potion": "Hp", "parent": 0},
+            {"potion": "Mp", "parent": 2},
+        ],
+    });
+
+    let mut world = World::new();
+    let mut loader = IncrementalLoad::<SaveFile>::new(&json).unwrap();
+    assert_eq!(loader.progress(), 0.0);
+
+    assert!(!loader.step(&mut world, LoadBudget::Entities(2)).unwrap());
+    assert_eq!(world.entities().len(), 2);
+    assert!(loader.progress() > 0.0);
+
+    assert!(!loader.step(&mut world, LoadBudget::Entities(2)).unwrap());
+    assert_eq!(world.entities().len(), 3);
+    assert_eq!(world.resource::<Gold>(), &Gold(100));
+
+    assert!(loader.step(&mut world, LoadBudget::Entities(2)).unwrap());
+    assert!(loader.is_finished());
+    assert_eq!(loader.progress(), 1.0);
+    assert_eq!(world.entities().len(), 5);
+
+    let mut query = world.query::<(&Potion, &Parent)>();
+    for (potion, parent) in query.iter(&world) {
+        let unit = world.get::<Unit>(parent.get()).unwrap();
+        match potion.0.as_str() {
+            "Hp" => assert_eq!(unit.0, "Bob"),
+            "Mp" => assert_eq!(unit.0, "Eric"),
+            _ => unreachable!(),
+        }
+    }
+
+    let mut world = World::new();
+    let mut loader = IncrementalLoad::<SaveFile>::new(&json).unwrap();
+    while !loader
+        .step(&mut world, LoadBudget::Time(Duration::ZERO))
+        .unwrap()
+    {}
+    assert_eq!(world.entities().len(), 5);
+    let mut query = world.query::<&Children>();
+    assert_eq!(query.iter(&world).count(), 2);
+}
+
+#[derive(BevyObject)]
+pub struct SerializeSquad {
+    unit: Unit,
+    #[serde(default)]
+    potions: ChildVec<Potion>,
+}
+
+#[test]
+pub fn test_budget() {
+    let json = json!([
+        {"unit": "Bob", "potions": ["Hp", "Mp"]},
+        {"unit": "Alice"},
+        {"unit": "Eric", "potions": ["Hp"]},
+        {"unit": 1},
+        {"unit": "Dan"},
+    ]);
+
+    let mut world = World::new();
+    let mut loader = IncrementalLoad::<SerializeSquad>::new(&json).unwrap();
+
+    // Children count towards the budget.
+    assert!(!loader.step(&mut world, LoadBudget::Entities(2)).unwrap());
+    assert_eq!(world.entities().len(), 3);
+    assert!(!loader.step(&mut world, LoadBudget::Entities(2)).unwrap());
+    assert_eq!(world.entities().len(), 6);
+
+    assert!(loader.step(&mut world, LoadBudget::Entities(2)).is_err());
+    assert!(loader.is_failed());
+    assert!(!loader.is_finished());
+    // The loader stays failed.
+    assert!(loader.step(&mut world, LoadBudget::Entities(2)).is_err());
+    assert!(!loader.is_finished());
+    assert_eq!(world.entities().len(), 6);
+}
+
+#[test]
+pub fn test_labels() {
+    let json = json!({
+        "SerializeUnit": [
+            {"entity": "bob", "unit": "Bob"},
+            {"entity": "eric", "unit": "Eric"},
+        ],
+        "SerializePotion": [
+            {"potion": "Hp", "parent": "bob"},
+            {"potion": "Mp", "parent": "eric"},
+        ],
+    });
+
+    let mut world = World::new();
+    let mut loader = IncrementalLoad::<SaveFile>::new(&json).unwrap();
+    assert!(!loader.step(&mut world, LoadBudget::Entities(2)).unwrap());
+
+    // Another load between steps does not affect labels of the loader.
+    let mut other = World::new();
+    other
+        .load::<SerializeUnit, _>(&json!([{"entity": "bob", "unit": "Dan"}]))
+        .unwrap();
+
+    while !loader.step(&mut world, LoadBudget::Entities(1)).unwrap() {}
+    assert_eq!(world.entities().len(), 4);
+    let mut query = world.query::<(&Potion, &Parent)>();
+    for (potion, parent) in query.iter(&world) {
+        let unit = world.get::<Unit>(parent.get()).unwrap();
+        match potion.0.as_str() {
+            "Hp" => assert_eq!(unit.0, "Bob"),
+            "Mp" => assert_eq!(unit.0, "Eric"),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use bevy_ecs::{component::Component, entity::Entity, world::World};
+use bevy_hierarchy::Parent;
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    batch, entity::OptionEntityPtr, BevyObject, EntityId, EntityRefMode, Parented, WorldExtension,
+};
+use postcard::ser_flavors::Flavor;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Door(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Switch(#[serde(with = "OptionEntityPtr")] Option<Entity>);
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeDoor {
+    id: EntityId,
+    door: Door,
+}
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeSwitch {
+    switch: Switch,
+    parent: Parented,
+}
+
+type Level = batch!(SerializeSwitch, SerializeDoor);
+
+const LEVEL: &str = r#"{
+    "SerializeSwitch": [
+        (switch: Some("boss_door"), parent: "boss_door"),
+        (switch: None, parent: 12),
+    ],
+    "SerializeDoor": [
+        (id: "boss_door", door: "Boss"),
+        (id: 12, door: "Exit"),
+    ],
+}"#;
+
+fn find(world: &mut World, name: &str) -> Entity {
+    world
+        .query::<(Entity, &Door)>()
+        .iter(world)
+        .find(|(_, x)| x.0 == name)
+        .unwrap()
+        .0
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.insert_resource(EntityRefMode::Deferred);
+    let report = world
+        .load::<Level, _>(&mut ron::Deserializer::from_str(LEVEL).unwrap())
+        .unwrap();
+    let boss = find(&mut world, "Boss");
+    let exit = find(&mut world, "Exit");
+    assert_eq!(report.labeled("boss_door"), Some(boss));
+    assert_eq!(report.entity(12), Some(exit));
+    assert_eq!(report.entity_map.len(), 1);
+    let switches: Vec<_> = world
+        .query::<(&Switch, &Parent)>()
+        .iter(&world)
+        .map(|(switch, parent)| (switch.0, parent.get()))
+        .collect();
+    assert!(switches.contains(&(Some(boss), boss)));
+    assert!(switches.contains(&(None, exit)));
+
+    // Binary formats write integers.
+    let mut serializer = postcard::Serializer {
+        output: postcard::ser_flavors::AllocVec::new(),
+    };
+    world.save::<Level, _>(&mut serializer).unwrap();
+    let bytes = serializer.output.finalize().unwrap();
+    world.despawn_bound_objects::<Level>();
+    world
+        .load::<Level, _>(&mut postcard::Deserializer::from_bytes(&bytes))
+        .unwrap();
+    let boss = find(&mut world, "Boss");
+    assert_eq!(world.query::<&Switch>().iter(&world).count(), 2);
+    assert!(world
+        .query::<&Switch>()
+        .iter(&world)
+        .any(|x| x.0 == Some(boss)));
+
+    let err = world
+        .load::<Level, _>(
+            &mut ron::Deserializer::from_str(
+                r#"{"SerializeSwitch": [(switch: Some("gate"), parent: 12)]}"#,
+            )
+            .unwrap(),
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), r#"Entity "gate" not serialized."#);
+}
@@ -0,0 +1,85 @@
+use bevy_ecs::{
+    component::Component,
+    entity::{Entity, EntityMapper, MapEntities},
+    world::World,
+};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{BevyObject, EntityId, WorldExtension};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
+
+fn raw<S: Serializer>(entities: &[Entity], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(entities.iter().map(|x| x.to_bits()))
+}
+
+fn from_raw<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Entity>, D::Error> {
+    Ok(Vec::<u64>::deserialize(deserializer)?
+        .into_iter()
+        .map(Entity::from_bits)
+        .collect())
+}
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+/// Serializes raw entities, like a third party component.
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Allies(#[serde(serialize_with = "raw", deserialize_with = "from_raw")] Vec<Entity>);
+
+impl MapEntities for Allies {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        for entity in &mut self.0 {
+            *entity = entity_mapper.map_entity(*entity);
+        }
+    }
+}
+
+#[derive(BevyObject)]
+pub struct SerializeUnit {
+    id: EntityId,
+    unit: Unit,
+    allies: Allies,
+}
+
+fn find(world: &mut World, name: &str) -> Entity {
+    world
+        .query::<(Entity, &Unit)>()
+        .iter(world)
+        .find(|(_, x)| x.0 == name)
+        .unwrap()
+        .0
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    let alice = world.spawn(Unit("Alice".to_owned())).id();
+    let bob = world
+        .spawn((Unit("Bob".to_owned()), Allies(vec![alice])))
+        .id();
+    world.entity_mut(alice).insert(Allies(vec![bob, alice]));
+    let value = serde_json::to_value(world.serialize_lens::<SerializeUnit>()).unwrap();
+
+    let mut world = World::new();
+    world.spawn_empty();
+    world.register_map_entities::<Allies>();
+    world.load::<SerializeUnit, _>(&value).unwrap();
+    let alice = find(&mut world, "Alice");
+    let bob = find(&mut world, "Bob");
+    assert_eq!(world.get::<Allies>(alice).unwrap().0, vec![bob, alice]);
+    assert_eq!(world.get::<Allies>(bob).unwrap().0, vec![alice]);
+    assert_eq!(world.entities().len(), 3);
+
+    let err = world
+        .load::<SerializeUnit, _>(&json!([
+            {"id": 1, "unit": "Carol", "allies": [1u64 << 40]},
+        ]))
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Entity {} not serialized.", 1u64 << 40)
+    );
+    assert_eq!(world.entities().len(), 3);
+}
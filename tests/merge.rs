@@ -0,0 +1,225 @@
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    reflect::{AppTypeRegistry, ReflectComponent},
+    world::World,
+};
+use bevy_hierarchy::{BuildWorldChildren, Children};
+use bevy_reflect::{Reflect, TypePath};
+use bevy_serde_lens::{
+    current_entity, entity::EntityPtr, with_world_mut, BevyObject, ChildVec, EntityId, Maybe,
+    WorldExtension,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq, Eq, Hash, Clone)]
+#[serde(transparent)]
+pub struct Id(u32);
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Hp(u32);
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Shield(u32);
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Potion(String);
+
+#[derive(Debug, Component, PartialEq)]
+pub struct Runtime(u32);
+
+#[derive(BevyObject)]
+pub struct Unit {
+    id: Id,
+    hp: Hp,
+    #[serde(default)]
+    shield: Maybe<Shield>,
+    #[serde(default)]
+    potions: ChildVec<Potion>,
+}
+
+/// Inserts [`Xp`] on the current entity when deserialized.
+#[derive(Debug, Serialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Level(u32);
+
+impl<'de> Deserialize<'de> for Level {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let level = u32::deserialize(deserializer)?;
+        let entity = current_entity().map_err(serde::de::Error::custom)?;
+        with_world_mut(|world| world.entity_mut(entity).insert(Xp(level * 100)).id())
+            .map_err(serde::de::Error::custom)?;
+        Ok(Level(level))
+    }
+}
+
+#[derive(Debug, Component, Reflect, PartialEq, Default)]
+#[reflect(Component)]
+pub struct Xp(u32);
+
+#[derive(BevyObject)]
+pub struct LeveledUnit {
+    id: Id,
+    level: Level,
+}
+
+#[derive(BevyObject)]
+pub struct Buff {
+    shield: Shield,
+    #[serde(default)]
+    potions: ChildVec<Potion>,
+}
+
+#[derive(BevyObject)]
+pub struct BuffedUnit {
+    id: Id,
+    #[serde(default)]
+    buff: Maybe<Buff>,
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    let a = world
+        .spawn((Id(0), Hp(10), Shield(5), Runtime(1)))
+        .with_children(|b| {
+            b.spawn(Potion("Hp".to_owned()));
+        })
+        .id();
+    let b = world.spawn((Id(1), Hp(20), Runtime(2))).id();
+
+    world
+        .load_merge::<Unit, Id, _>(json!([
+            {"id": 0, "hp": 15, "shield": null, "potions": ["Mp", "Fire"]},
+            {"id": 1, "hp": 25, "shield": 3},
+            {"id": 2, "hp": 30},
+        ]))
+        .unwrap();
+
+    assert_eq!(world.get::<Hp>(a), Some(&Hp(15)));
+    assert_eq!(world.get::<Shield>(a), None);
+    assert_eq!(world.get::<Runtime>(a), Some(&Runtime(1)));
+    assert_eq!(world.get::<Children>(a).unwrap().len(), 2);
+
+    assert_eq!(world.get::<Hp>(b), Some(&Hp(25)));
+    assert_eq!(world.get::<Shield>(b), Some(&Shield(3)));
+    assert_eq!(world.get::<Runtime>(b), Some(&Runtime(2)));
+
+    // 3 units and 2 potions.
+    assert_eq!(world.entities().len(), 5);
+
+    let mut query = world.query::<(&Id, &Hp)>();
+    assert!(query
+        .iter(&world)
+        .any(|(id, hp)| id == &Id(2) && hp == &Hp(30)));
+
+    assert!(world
+        .load_merge::<Unit, Id, _>(json!([{"id": 0, "hp": "bad"}]))
+        .is_err());
+    assert_eq!(world.get::<Hp>(a), Some(&Hp(15)));
+    assert_eq!(world.entities().len(), 5);
+}
+
+#[test]
+pub fn test_remove_derived() {
+    let mut world = World::new();
+    let a = world
+        .spawn((Id(0), Shield(5), Runtime(1)))
+        .with_children(|b| {
+            b.spawn(Potion("Hp".to_owned()));
+        })
+        .id();
+    let b = world.spawn((Id(1), Runtime(2))).id();
+
+    world
+        .load_merge::<BuffedUnit, Id, _>(json!([
+            {"id": 0, "buff": null},
+            {"id": 1, "buff": {"shield": 3, "potions": ["Mp"]}},
+        ]))
+        .unwrap();
+
+    assert_eq!(world.get::<Shield>(a), None);
+    assert!(world.get::<Children>(a).is_none_or(|x| x.is_empty()));
+    assert_eq!(world.get::<Runtime>(a), Some(&Runtime(1)));
+
+    assert_eq!(world.get::<Shield>(b), Some(&Shield(3)));
+    assert_eq!(world.get::<Children>(b).unwrap().len(), 1);
+    assert_eq!(world.get::<Runtime>(b), Some(&Runtime(2)));
+
+    // 2 units and 1 potion.
+    assert_eq!(world.entities().len(), 3);
+}
+
+#[test]
+pub fn test_reflected() {
+    let mut world = World::new();
+    world.init_resource::<AppTypeRegistry>();
+    world.resource::<AppTypeRegistry>().write().register::<Xp>();
+    let a = world.spawn((Id(0), Level(1), Xp(100), Runtime(1))).id();
+
+    world
+        .load_merge::<LeveledUnit, Id, _>(json!([{"id": 0, "level": 2}]))
+        .unwrap();
+
+    assert_eq!(world.get::<Level>(a), Some(&Level(2)));
+    assert_eq!(world.get::<Xp>(a), Some(&Xp(200)));
+    assert_eq!(world.get::<Runtime>(a), Some(&Runtime(1)));
+    assert_eq!(world.entities().len(), 1);
+}
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Owner(#[serde(with = "EntityPtr")] Entity);
+
+#[derive(BevyObject)]
+pub struct OwnedItem {
+    owner: Owner,
+}
+
+#[derive(BevyObject)]
+pub struct OwnerUnit {
+    entity: EntityId,
+    id: Id,
+    items: ChildVec<OwnedItem>,
+}
+
+#[derive(BevyObject)]
+pub struct LateKeyUnit {
+    entity: EntityId,
+    items: ChildVec<OwnedItem>,
+    id: Id,
+}
+
+#[test]
+pub fn test_self_reference() {
+    let mut world = World::new();
+    let a = world.spawn(Id(0)).id();
+
+    world
+        .load_merge::<OwnerUnit, Id, _>(json!([
+            {"entity": 7, "id": 0, "items": [{"owner": 7}]},
+            {"entity": 8, "id": 1, "items": [{"owner": 8}, {"owner": 7}]},
+        ]))
+        .unwrap();
+    let mut query = world.query::<&Owner>();
+    assert_eq!(query.iter(&world).count(), 3);
+    for owner in query.iter(&world) {
+        assert!(world.get_entity(owner.0).is_some());
+    }
+    let children = world.get::<Children>(a).unwrap();
+    assert_eq!(world.get::<Owner>(children[0]).unwrap().0, a);
+
+    // The merge target is unknown when `owner` is deserialized.
+    let len = world.entities().len();
+    let err = world
+        .load_merge::<LateKeyUnit, Id, _>(json!([
+            {"entity": 9, "items": [{"owner": 9}], "id": 0},
+        ]))
+        .unwrap_err();
+    assert!(err.to_string().contains("is referenced before its key"));
+    assert_eq!(world.entities().len(), len);
+}
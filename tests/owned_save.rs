@@ -0,0 +1,106 @@
+use bevy_ecs::{component::Component, system::Resource, world::World};
+use bevy_hierarchy::BuildWorldChildren;
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{batch, BevyObject, ChildVec, Maybe, SerializeResource, WorldExtension};
+use postcard::ser_flavors::Flavor;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Weapon(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Potion(String);
+
+#[derive(Serialize, Deserialize, Resource, TypePath)]
+pub struct Terrain {
+    width: u32,
+    height: u32,
+}
+
+#[derive(BevyObject)]
+pub struct SerializeUnit {
+    unit: Unit,
+    #[serde(default)]
+    #[bevy_object(no_filter)]
+    weapon: Maybe<Weapon>,
+    #[serde(default)]
+    potions: ChildVec<Potion>,
+}
+
+type SaveFile = batch!(SerializeUnit, SerializeResource<Terrain>);
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.insert_resource(Terrain {
+        width: 16,
+        height: 9,
+    });
+    world
+        .spawn((Unit("Bob".to_owned()), Weapon("Sword".to_owned())))
+        .with_children(|b| {
+            b.spawn(Potion("Hp".to_owned()));
+            b.spawn(Potion("Mp".to_owned()));
+        });
+    world.spawn(Unit("Alice".to_owned()));
+
+    let mut vec = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut vec);
+    world.save::<SaveFile, _>(&mut serializer).unwrap();
+    let json = String::from_utf8(vec).unwrap();
+    let mut serializer = postcard::Serializer {
+        output: postcard::ser_flavors::AllocVec::new(),
+    };
+    world.save::<SaveFile, _>(&mut serializer).unwrap();
+    let bytes = serializer.output.finalize().unwrap();
+
+    let human = world.save_owned::<SaveFile>(true).unwrap();
+    let binary = world.save_owned::<SaveFile>(false).unwrap();
+
+    let (json2, bytes2) = std::thread::spawn(move || {
+        assert!(postcard::to_allocvec(&human).is_err());
+        (
+            serde_json::to_string(&human).unwrap(),
+            postcard::to_allocvec(&binary).unwrap(),
+        )
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(json, json2);
+    assert_eq!(bytes, bytes2);
+
+    world.despawn_bound_objects::<SaveFile>();
+    assert_eq!(world.entities().len(), 0);
+
+    let mut deserializer = postcard::Deserializer::from_bytes(&bytes2);
+    world.load::<SaveFile, _>(&mut deserializer).unwrap();
+    assert_eq!(world.entities().len(), 4);
+    assert_eq!(world.resource::<Terrain>().width, 16);
+}
+
+#[test]
+pub fn test_single() {
+    let mut world = World::new();
+    world.spawn(Unit("Bob".to_owned())).with_children(|b| {
+        b.spawn(Potion("Hp".to_owned()));
+    });
+
+    let mut serializer = postcard::Serializer {
+        output: postcard::ser_flavors::AllocVec::new(),
+    };
+    world.save::<SerializeUnit, _>(&mut serializer).unwrap();
+    let bytes = serializer.output.finalize().unwrap();
+
+    let binary = world.save_owned::<SerializeUnit>(false).unwrap();
+    let bytes2 = std::thread::spawn(move || postcard::to_allocvec(&binary).unwrap())
+        .join()
+        .unwrap();
+    assert_eq!(bytes, bytes2);
+}
@@ -0,0 +1,198 @@
+use bevy_ecs::{component::Component, entity::Entity, world::World};
+use bevy_hierarchy::{BuildWorldChildren, Children, Parent};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    entity::EntityPtr, BevyObject, EntityId, Maybe, ParentObject, WorldExtension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath, PartialEq, Eq, Hash, Clone)]
+#[serde(transparent)]
+pub struct Team(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Player(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Npc(String);
+
+#[derive(BevyObject)]
+pub struct SerializePlayer {
+    player: Player,
+    team: ParentObject<Team, Team>,
+}
+
+#[derive(BevyObject)]
+pub struct SerializeNpc {
+    npc: Npc,
+    #[serde(default)]
+    team: Maybe<ParentObject<Team, Team>>,
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world
+        .spawn(Team("Red".to_owned()))
+        .with_children(|builder| {
+            builder.spawn(Player("Alice".to_owned()));
+            builder.spawn(Player("Bob".to_owned()));
+        });
+    world
+        .spawn(Team("Blue".to_owned()))
+        .with_children(|builder| {
+            builder.spawn(Player("Carol".to_owned()));
+        });
+    let value = serde_json::to_value(world.serialize_lens::<SerializePlayer>()).unwrap();
+    assert_eq!(
+        value,
+        json!([
+            {"player": "Alice", "team": "Red"},
+            {"player": "Bob", "team": "Red"},
+            {"player": "Carol", "team": "Blue"},
+        ])
+    );
+
+    world.clear_entities();
+    let report = world.load::<SerializePlayer, _>(&value).unwrap();
+    assert_eq!(world.entities().len(), 5);
+    assert_eq!(report.parents.len(), 2);
+    let value2 = serde_json::to_value(world.serialize_lens::<SerializePlayer>()).unwrap();
+    assert_eq!(value, value2);
+    let (_, children) = world
+        .query::<(&Team, &Children)>()
+        .iter(&world)
+        .find(|(team, _)| team.0 == "Red")
+        .unwrap();
+    assert_eq!(children.len(), 2);
+
+    // Attaches to the existing team.
+    let report = world
+        .load::<SerializePlayer, _>(&json!([{"player": "Dave", "team": "Blue"}]))
+        .unwrap();
+    assert_eq!(world.entities().len(), 6);
+    assert!(report.parents.is_empty());
+    let (_, parent) = world
+        .query::<(&Player, &Parent)>()
+        .iter(&world)
+        .find(|(player, _)| player.0 == "Dave")
+        .unwrap();
+    assert_eq!(world.get::<Team>(parent.get()).unwrap().0, "Blue");
+
+    world.clear_entities();
+    world
+        .spawn(Team("Red".to_owned()))
+        .with_children(|builder| {
+            builder.spawn(Npc("Guard".to_owned()));
+        });
+    world.spawn(Npc("Merchant".to_owned()));
+    let value = serde_json::to_value(world.serialize_lens::<SerializeNpc>()).unwrap();
+    assert_eq!(
+        value,
+        json!([
+            {"npc": "Guard", "team": "Red"},
+            {"npc": "Merchant", "team": null},
+        ])
+    );
+    world.clear_entities();
+    world.load::<SerializeNpc, _>(&value).unwrap();
+    assert_eq!(world.entities().len(), 3);
+    let value2 = serde_json::to_value(world.serialize_lens::<SerializeNpc>()).unwrap();
+    assert_eq!(value, value2);
+
+    world.clear_entities();
+    world.spawn(Player("Eve".to_owned()));
+    let value = serde_json::to_value(world.serialize_lens::<SerializePlayer>()).unwrap();
+    assert_eq!(value, json!([]));
+}
+
+#[test]
+pub fn test_cleanup() {
+    let mut world = World::new();
+    let value = json!([
+        {"player": "Alice", "team": "Red"},
+        {"player": "Bob", "team": "Blue"},
+    ]);
+    world.load::<SerializePlayer, _>(&value).unwrap();
+    assert_eq!(world.entities().len(), 4);
+
+    // Loaded teams are replaced, not duplicated.
+    world
+        .load_replace::<SerializePlayer, _>(&json!([{"player": "Carol", "team": "Red"}]))
+        .unwrap();
+    assert_eq!(world.entities().len(), 2);
+    assert_eq!(world.query::<&Team>().iter(&world).count(), 1);
+
+    // Teams not spawned by a load are kept.
+    let green = world.spawn(Team("Green".to_owned())).id();
+    world
+        .load::<SerializePlayer, _>(&json!([{"player": "Dave", "team": "Green"}]))
+        .unwrap();
+    world.despawn_bound_objects::<SerializePlayer>();
+    assert_eq!(world.entities().len(), 1);
+    assert!(world.get_entity(green).is_some());
+
+    // Spawned teams are despawned on rollback.
+    let err = world.load::<SerializePlayer, _>(&json!([
+        {"player": "Eve", "team": "Red"},
+        {"player": 1, "team": "Blue"},
+    ]));
+    assert!(err.is_err());
+    assert_eq!(world.entities().len(), 1);
+}
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Score(u32);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Rival(#[serde(with = "EntityPtr")] Entity);
+
+#[derive(BevyObject)]
+pub struct SerializeTeam {
+    id: EntityId,
+    team: Team,
+    score: Score,
+}
+
+#[derive(BevyObject)]
+pub struct SerializeMember {
+    player: Player,
+    team: ParentObject<SerializeTeam, Team>,
+    #[serde(default)]
+    rival: Maybe<Rival>,
+}
+
+#[test]
+pub fn test_matched_ids() {
+    let mut world = World::new();
+    let red = world.spawn((Team("Red".to_owned()), Score(0))).id();
+
+    // Parents that fail to load are not left behind.
+    let err = world.load::<SerializeMember, _>(&json!([
+        {"player": "Alice", "team": {"id": 5, "team": "Red", "score": "bad"}},
+    ]));
+    assert!(err.is_err());
+    assert_eq!(world.entities().len(), 1);
+
+    // The id of a matched parent points to the existing parent.
+    world
+        .load::<SerializeMember, _>(&json!([
+            {"player": "Alice", "team": {"id": 5, "team": "Red", "score": 1}},
+            {"player": "Bob", "team": {"id": 6, "team": "Blue", "score": 2}, "rival": 5},
+        ]))
+        .unwrap();
+    assert_eq!(world.entities().len(), 4);
+    let (_, rival) = world
+        .query::<(&Player, &Rival)>()
+        .iter(&world)
+        .find(|(player, _)| player.0 == "Bob")
+        .unwrap();
+    assert_eq!(rival.0, red);
+    assert_eq!(world.get::<Score>(red).unwrap().0, 0);
+    assert_eq!(world.get::<Children>(red).unwrap().len(), 1);
+}
@@ -0,0 +1,164 @@
+use bevy_ecs::{component::Component, entity::Entity, world::World};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    batch,
+    entity::EntityPtr,
+    path::PathSegment,
+    typetagged::{IntoTypeTagged, TraitObject, TypeTagged},
+    BevyObject, ChildVec, EntityId, WorldExtension,
+};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Name(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Potion(u32);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+pub struct Target(#[serde(with = "EntityPtr")] Entity);
+
+pub trait Effect: Send + Sync + 'static {
+    fn as_ser(&self) -> &dyn erased_serde::Serialize;
+}
+
+impl TraitObject for dyn Effect {
+    fn name(&self) -> impl AsRef<str> {
+        "Heal"
+    }
+
+    fn as_serialize(&self) -> &dyn erased_serde::Serialize {
+        self.as_ser()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Heal(u32);
+
+impl Effect for Heal {
+    fn as_ser(&self) -> &dyn erased_serde::Serialize {
+        self
+    }
+}
+
+impl IntoTypeTagged<Box<dyn Effect>> for Heal {
+    fn name() -> impl AsRef<str> {
+        "Heal"
+    }
+
+    fn into_type_tagged(self) -> Box<dyn Effect> {
+        Box::new(self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Buff(TypeTagged<Box<dyn Effect>>);
+
+#[derive(BevyObject)]
+pub struct Character {
+    id: EntityId,
+    name: Name,
+    potions: ChildVec<Potion>,
+}
+
+#[derive(BevyObject)]
+pub struct Enemy {
+    target: Target,
+}
+
+#[derive(BevyObject)]
+pub struct Spell {
+    buff: Buff,
+}
+
+type SaveFile = batch!(Character, Enemy, Spell);
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.register_typetag::<Box<dyn Effect>, Heal>();
+
+    let value = json!({
+        "Character": [
+            {"id": 1, "name": "Alice", "potions": [1, 2]},
+            {"id": 2, "name": "Bob", "potions": ["Hp", 3]},
+        ],
+        "Enemy": [],
+        "Spell": [],
+    });
+    let err = world.load::<SaveFile, _>(&value).unwrap_err();
+    assert_eq!(err.path.to_string(), "Character[1].potions[0].Potion");
+    assert_eq!(
+        err.path.segments(),
+        [
+            PathSegment::Section("Character"),
+            PathSegment::Index(1),
+            PathSegment::Field("potions"),
+            PathSegment::Index(0),
+            PathSegment::Type(std::any::type_name::<Potion>()),
+        ]
+    );
+    assert!(err
+        .to_string()
+        .starts_with("Character[1].potions[0].Potion: "));
+    assert_eq!(world.entities().len(), 0);
+
+    let value = json!({
+        "Character": [
+            {"id": 1, "name": "Alice", "potions": []},
+        ],
+        "Enemy": [
+            {"target": 1},
+            {"target": 5},
+        ],
+        "Spell": [],
+    });
+    let err = world.load::<SaveFile, _>(&value).unwrap_err();
+    assert_eq!(err.path.to_string(), "Enemy[1].target.Target");
+    assert_eq!(err.error.to_string(), "Entity 5 not serialized.");
+
+    let value = json!({
+        "Character": [],
+        "Enemy": [],
+        "Spell": [
+            {"buff": {"Heal": 4}},
+            {"buff": {"Heal": "big"}},
+        ],
+    });
+    let err = world.load::<SaveFile, _>(&value).unwrap_err();
+    assert_eq!(err.path.to_string(), "Spell[1].buff.Buff.Heal");
+
+    let value = json!({
+        "Character": [],
+        "Enemy": [],
+        "Spell": [
+            {"buff": {"Poison": 4}},
+        ],
+    });
+    let err = world.load::<SaveFile, _>(&value).unwrap_err();
+    assert_eq!(err.path.to_string(), "Spell[0].buff.Buff");
+
+    let err = world
+        .deserialize_lens::<SaveFile>()
+        .deserialize(&json!({"Character": [{"id": 1, "name": 0}]}))
+        .unwrap_err();
+    assert!(err.to_string().starts_with("Character[0].name.Name: "));
+
+    let value = json!({
+        "Character": [
+            {"id": 1, "name": "Alice", "potions": [1, 2]},
+        ],
+        "Enemy": [
+            {"target": 1},
+        ],
+        "Spell": [
+            {"buff": {"Heal": 4}},
+        ],
+    });
+    world.load::<SaveFile, _>(&value).unwrap();
+    assert_eq!(world.entities().len(), 5);
+}
@@ -0,0 +1,156 @@
+use bevy_ecs::{component::Component, entity::Entity, world::World};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    batch,
+    persistent::{OptionPersistentPtr, PersistentPtr},
+    BevyObject, EntityRefMode, PersistentId, PersistentIds, WorldExtension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath, PartialEq, Eq, Hash, Clone)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Leader(#[serde(with = "PersistentPtr")] Entity);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Rival(#[serde(with = "OptionPersistentPtr")] Option<Entity>);
+
+#[derive(BevyObject)]
+pub struct SerializeUnit {
+    #[serde(default)]
+    id: PersistentId,
+    unit: Unit,
+}
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeFollower {
+    id: PersistentId,
+    leader: Leader,
+    rival: Rival,
+}
+
+type SaveFile = batch!(SerializeFollower, SerializeUnit);
+
+fn find(world: &mut World, name: &str) -> Entity {
+    world
+        .query::<(Entity, &Unit)>()
+        .iter(world)
+        .find(|(_, x)| x.0 == name)
+        .unwrap()
+        .0
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.init_resource::<PersistentIds>();
+    let alice = world
+        .spawn((PersistentId::default(), Unit("Alice".to_owned())))
+        .id();
+    let bob = world
+        .spawn((PersistentId::default(), Unit("Bob".to_owned())))
+        .id();
+    assert_eq!(world.get::<PersistentId>(alice), Some(&PersistentId(1)));
+    assert_eq!(world.get::<PersistentId>(bob), Some(&PersistentId(2)));
+    assert_eq!(
+        world.resource::<PersistentIds>().get(PersistentId(2)),
+        Some(bob)
+    );
+    world.spawn((PersistentId(10), Leader(alice), Rival(Some(bob))));
+
+    let value = serde_json::to_value(world.serialize_lens::<SaveFile>()).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "SerializeFollower": [{"id": 10, "leader": 1, "rival": 2}],
+            "SerializeUnit": [{"id": 1, "unit": "Alice"}, {"id": 2, "unit": "Bob"}],
+        })
+    );
+
+    // Ids stay the same across sessions.
+    let mut world = World::new();
+    world.insert_resource(EntityRefMode::Deferred);
+    let report = world.load::<SaveFile, _>(&value).unwrap();
+    // Persistent ids are not `EntityId`s.
+    assert!(report.entity_map.is_empty());
+    let alice = find(&mut world, "Alice");
+    let bob = find(&mut world, "Bob");
+    assert_eq!(world.entities().len(), 3);
+    let (id, leader, rival) = world
+        .query::<(&PersistentId, &Leader, &Rival)>()
+        .single(&world);
+    assert_eq!(*id, PersistentId(10));
+    assert_eq!(leader.0, alice);
+    assert_eq!(rival.0, Some(bob));
+    assert_eq!(
+        world.resource::<PersistentIds>().get(PersistentId(1)),
+        Some(alice)
+    );
+    assert_eq!(world.resource::<PersistentIds>().len(), 3);
+
+    // References to entities outside of the batch, and new ids on missing fields.
+    world
+        .load::<SerializeFollower, _>(&json!([{"id": 11, "leader": 2, "rival": null}]))
+        .unwrap();
+    world
+        .load::<SerializeUnit, _>(&json!([{"unit": "Carol"}]))
+        .unwrap();
+    let carol = find(&mut world, "Carol");
+    assert_eq!(world.get::<PersistentId>(carol), Some(&PersistentId(12)));
+    world
+        .load::<SerializeUnit, _>(&json!([{"id": null, "unit": "Dan"}]))
+        .unwrap();
+    let dan = find(&mut world, "Dan");
+    assert_eq!(world.get::<PersistentId>(dan), Some(&PersistentId(13)));
+
+    world.despawn(bob);
+    assert_eq!(world.resource::<PersistentIds>().get(PersistentId(2)), None);
+    assert!(world
+        .load::<SerializeFollower, _>(&json!([{"id": 14, "leader": 2, "rival": null}]))
+        .is_err());
+    assert_eq!(world.resource::<PersistentIds>().len(), 5);
+}
+
+#[test]
+pub fn test_duplicates() {
+    let mut world = World::new();
+    let value = json!([{"id": 1, "unit": "Alice"}, {"id": 2, "unit": "Bob"}]);
+    world.load::<SerializeUnit, _>(&value).unwrap();
+    let alice = find(&mut world, "Alice");
+
+    // Loading the same save twice does not steal the ids.
+    let err = world.load::<SerializeUnit, _>(&value).unwrap_err();
+    assert!(err.to_string().contains("PersistentId 1 already exists"));
+    assert_eq!(world.entities().len(), 2);
+    assert_eq!(
+        world.resource::<PersistentIds>().get(PersistentId(1)),
+        Some(alice)
+    );
+
+    // Merged objects may carry the id of the entity they are merged into.
+    world
+        .load_merge::<SerializeUnit, PersistentId, _>(&json!([{"id": 1, "unit": "Alicia"}]))
+        .unwrap();
+    assert_eq!(world.get::<Unit>(alice).unwrap().0, "Alicia");
+    assert_eq!(
+        world.resource::<PersistentIds>().get(PersistentId(1)),
+        Some(alice)
+    );
+    assert!(world
+        .load_merge::<SerializeUnit, Unit, _>(&json!([{"id": 1, "unit": "Dan"}]))
+        .is_err());
+    assert_eq!(world.entities().len(), 2);
+
+    // Inserting a registered id keeps the first entity.
+    world.spawn((PersistentId(1), Unit("Carol".to_owned())));
+    assert_eq!(
+        world.resource::<PersistentIds>().get(PersistentId(1)),
+        Some(alice)
+    );
+}
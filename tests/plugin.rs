@@ -0,0 +1,105 @@
+#![cfg(feature = "plugin")]
+use bevy_app::App;
+use bevy_ecs::{
+    component::Component,
+    event::{Events, ManualEventReader},
+    system::Resource,
+    world::World,
+};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::plugin::{
+    FileError, LoadCompleted, LoadFailed, LoadRequest, SaveCompleted, SaveFailed, SaveFormat,
+    SaveRequest, SerdeLensPlugin,
+};
+use bevy_serde_lens::{batch, SerializeResource, WorldExtension};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Debug, Serialize, Deserialize, Resource, TypePath, PartialEq)]
+#[serde(transparent)]
+pub struct Gold(u32);
+
+type SaveFile = batch!(Unit, SerializeResource<Gold>);
+
+fn count<E: bevy_ecs::event::Event>(world: &World, reader: &mut ManualEventReader<E>) -> usize {
+    reader.read(world.resource::<Events<E>>()).count()
+}
+
+#[test]
+pub fn test() {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let dir = std::env::temp_dir().join(format!(
+        "bevy_serde_lens_plugin_test_{}_{nanos}",
+        std::process::id()
+    ));
+    let mut app = App::new();
+    app.add_plugins(SerdeLensPlugin::<SaveFile>::default());
+    app.world_mut().spawn(Unit("Bob".to_owned()));
+    app.world_mut().spawn(Unit("Alice".to_owned()));
+    app.world_mut().insert_resource(Gold(42));
+
+    let mut saved = ManualEventReader::<SaveCompleted<SaveFile>>::default();
+    let mut loaded = ManualEventReader::<LoadCompleted<SaveFile>>::default();
+    let mut failed = ManualEventReader::<LoadFailed<SaveFile>>::default();
+
+    for (file, format) in [
+        ("save.json", SaveFormat::Json),
+        ("save.ron", SaveFormat::Ron),
+        ("save.bin", SaveFormat::Postcard),
+    ] {
+        let path = dir.join(file);
+        app.world_mut()
+            .send_event(SaveRequest::<SaveFile>::new(&path, format));
+        app.update();
+        assert_eq!(count(app.world(), &mut saved), 1);
+        assert!(path.exists());
+
+        app.despawn_bound_objects::<SaveFile>();
+        assert_eq!(app.world().entities().len(), 0);
+
+        app.world_mut()
+            .send_event(LoadRequest::<SaveFile>::new(&path, format));
+        app.update();
+        assert_eq!(count(app.world(), &mut loaded), 1);
+        assert_eq!(app.world().entities().len(), 2);
+        assert_eq!(app.world().resource::<Gold>(), &Gold(42));
+    }
+
+    // A failed save does not replace the existing file.
+    let path = dir.join("save.json");
+    let bytes = std::fs::read(&path).unwrap();
+    app.world_mut().remove_resource::<Gold>();
+    app.world_mut()
+        .send_event(SaveRequest::<SaveFile>::new(&path, SaveFormat::Json));
+    app.update();
+    let mut save_failed = ManualEventReader::<SaveFailed<SaveFile>>::default();
+    let events: Vec<_> = save_failed
+        .read(app.world().resource::<Events<SaveFailed<SaveFile>>>())
+        .collect();
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0].error, FileError::Json(_)));
+    assert_eq!(count(app.world(), &mut saved), 0);
+    assert_eq!(std::fs::read(&path).unwrap(), bytes);
+    assert!(!dir.join("save.json.tmp").exists());
+
+    app.world_mut().send_event(LoadRequest::<SaveFile>::new(
+        dir.join("missing.json"),
+        SaveFormat::Json,
+    ));
+    app.update();
+    let events: Vec<_> = failed
+        .read(app.world().resource::<Events<LoadFailed<SaveFile>>>())
+        .collect();
+    assert_eq!(events.len(), 1);
+    assert!(
+        matches!(&events[0].error.error, FileError::Io(e) if e.kind() == std::io::ErrorKind::NotFound)
+    );
+    assert_eq!(count(app.world(), &mut loaded), 0);
+    let _ = std::fs::remove_dir_all(dir);
+}
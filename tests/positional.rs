@@ -0,0 +1,89 @@
+use bevy_ecs::{component::Component, system::Resource, world::World};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    batch, value::Value, BevyObject, IncrementalLoad, LoadBudget, Positional, SerializeResource,
+    WorldExtension,
+};
+use postcard::ser_flavors::Flavor;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Resource, TypePath)]
+#[serde(transparent)]
+pub struct Gold(u32);
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeUnit {
+    unit: Unit,
+}
+
+type SaveFile = batch!(SerializeUnit, SerializeResource<Gold>);
+
+fn postcard<T: bevy_serde_lens::BatchSerialization>(world: &mut World) -> Vec<u8> {
+    let mut serializer = postcard::Serializer {
+        output: postcard::ser_flavors::AllocVec::new(),
+    };
+    world.save::<T, _>(&mut serializer).unwrap();
+    serializer.output.finalize().unwrap()
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.spawn(Unit("Alice".to_owned()));
+    world.spawn(Unit("Bob".to_owned()));
+    world.insert_resource(Gold(7));
+
+    let value = serde_json::to_value(world.serialize_lens::<Positional<SaveFile>>()).unwrap();
+    assert_eq!(value, json!([[{"unit": "Alice"}, {"unit": "Bob"}], 7]));
+
+    let bytes = postcard::<Positional<SaveFile>>(&mut world);
+    assert!(bytes.len() < postcard::<SaveFile>(&mut world).len());
+
+    world.despawn_bound_objects::<SaveFile>();
+    assert_eq!(world.entities().len(), 0);
+    let report = world
+        .load::<Positional<SaveFile>, _>(&mut postcard::Deserializer::from_bytes(&bytes))
+        .unwrap();
+    assert_eq!(report.get("SerializeUnit").len(), 2);
+    assert_eq!(world.resource::<Gold>().0, 7);
+
+    world.load::<Positional<SaveFile>, _>(&value).unwrap();
+    assert_eq!(world.entities().len(), 4);
+    assert!(world
+        .load::<Positional<SaveFile>, _>(&json!([[{"unit": "Carol"}]]))
+        .is_err());
+    assert_eq!(world.entities().len(), 4);
+
+    let mut loader = IncrementalLoad::<Positional<SaveFile>>::new(&value).unwrap();
+    while !loader.step(&mut world, LoadBudget::Entities(1)).unwrap() {}
+    assert_eq!(world.entities().len(), 6);
+
+    let single = serde_json::to_value(world.serialize_lens::<Positional<SerializeUnit>>()).unwrap();
+    assert_eq!(
+        single,
+        serde_json::to_value(world.serialize_lens::<SerializeUnit>()).unwrap()
+    );
+
+    world.despawn_bound_objects::<SaveFile>();
+    world.register_migration("Gold", 1, |value| {
+        *value = Value::U32(100);
+        Ok(())
+    });
+    world
+        .load_versioned::<Positional<SaveFile>, _>(
+            2,
+            &json!({
+                "version": 1,
+                "data": [[{"unit": "Dan"}], 3],
+            }),
+        )
+        .unwrap();
+    assert_eq!(world.entities().len(), 1);
+    assert_eq!(world.resource::<Gold>().0, 100);
+}
@@ -0,0 +1,97 @@
+use bevy_ecs::{component::Component, system::Resource, world::World};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{batch, BevyObject, ChildVec, SerializeResource, WorldExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Potion(String);
+
+#[derive(Serialize, Deserialize, Resource, TypePath)]
+#[serde(transparent)]
+pub struct Gold(u32);
+
+#[derive(Serialize, Deserialize, Resource, TypePath)]
+#[serde(transparent)]
+pub struct Silver(u32);
+
+#[derive(BevyObject)]
+pub struct SerializeUnit {
+    unit: Unit,
+    potions: ChildVec<Potion>,
+}
+
+type SaveFile = batch!(
+    SerializeUnit,
+    SerializeResource<Gold>,
+    SerializeResource<Silver>
+);
+
+fn names(world: &mut World) -> Vec<String> {
+    let mut names: Vec<_> = world
+        .query::<&Unit>()
+        .iter(world)
+        .map(|x| x.0.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world
+        .load::<SaveFile, _>(&json!({
+            "SerializeUnit": [
+                {"unit": "Alice", "potions": ["Hp"]},
+                {"unit": "Bob", "potions": []},
+            ],
+            "Gold": 1,
+            "Silver": 2,
+        }))
+        .unwrap();
+    assert_eq!(world.entities().len(), 3);
+
+    // Failed replace changes nothing.
+    assert!(world
+        .load_replace::<SaveFile, _>(&json!({
+            "SerializeUnit": [
+                {"unit": "Carol", "potions": ["Mp"]},
+                {"unit": 4, "potions": []},
+            ],
+            "Gold": 5,
+        }))
+        .is_err());
+    assert_eq!(names(&mut world), ["Alice", "Bob"]);
+    assert_eq!(world.entities().len(), 3);
+    assert_eq!(world.resource::<Gold>().0, 1);
+    assert_eq!(world.resource::<Silver>().0, 2);
+
+    let report = world
+        .load_replace::<SaveFile, _>(&json!({
+            "SerializeUnit": [
+                {"unit": "Carol", "potions": ["Mp", "Hp"]},
+            ],
+            "Gold": 5,
+        }))
+        .unwrap();
+    assert_eq!(report.get("SerializeUnit").len(), 1);
+    assert_eq!(names(&mut world), ["Carol"]);
+    assert_eq!(world.entities().len(), 3);
+    assert_eq!(world.resource::<Gold>().0, 5);
+    assert!(!world.contains_resource::<Silver>());
+
+    world
+        .load_replace::<SerializeUnit, _>(&json!([
+            {"unit": "Dan", "potions": []},
+        ]))
+        .unwrap();
+    assert_eq!(names(&mut world), ["Dan"]);
+    assert_eq!(world.entities().len(), 1);
+    assert_eq!(world.resource::<Gold>().0, 5);
+}
@@ -0,0 +1,74 @@
+use bevy_ecs::{component::Component, system::Resource, world::World};
+use bevy_hierarchy::Parent;
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{batch, BevyObject, EntityId, Parented, SerializeResource, WorldExtension};
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Potion(String);
+
+#[derive(Serialize, Deserialize, Resource, TypePath)]
+#[serde(transparent)]
+pub struct Gold(u32);
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeUnit {
+    entity: EntityId,
+    unit: Unit,
+}
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializePotion {
+    potion: Potion,
+    parent: Parented,
+}
+
+type SaveFile = batch!(SerializeUnit, SerializeResource<Gold>, SerializePotion);
+
+#[test]
+pub fn test() {
+    let json = json!({
+        "SerializeUnit": [
+            {"entity": 7, "unit": "Bob"},
+            {"entity": 9, "unit": "Alice"},
+        ],
+        "Gold": 100,
+        "SerializePotion": [
+            {"potion": "Hp", "parent": 9},
+        ],
+    });
+
+    let mut world = World::new();
+    let report = world.load::<SaveFile, _>(&json).unwrap();
+
+    let units = report.get("SerializeUnit");
+    assert_eq!(units.len(), 2);
+    assert_eq!(world.get::<Unit>(units[0]).unwrap().0, "Bob");
+    assert_eq!(world.get::<Unit>(units[1]).unwrap().0, "Alice");
+    assert_eq!(report.entity(7), Some(units[0]));
+    assert_eq!(report.entity(9), Some(units[1]));
+    assert_eq!(report.entity_map.len(), 2);
+
+    let potions = report.get("SerializePotion");
+    assert_eq!(potions.len(), 1);
+    assert_eq!(world.get::<Parent>(potions[0]).unwrap().get(), units[1]);
+
+    assert_eq!(report.resources, vec!["Gold"]);
+    assert!(report.get("Gold").is_empty());
+
+    let report = world
+        .deserialize_lens::<SaveFile>()
+        .deserialize(&json)
+        .unwrap();
+    assert_eq!(report.get("SerializeUnit").len(), 2);
+    assert_ne!(report.get("SerializeUnit"), units);
+    assert_eq!(world.entities().len(), 6);
+}
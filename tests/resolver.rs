@@ -0,0 +1,62 @@
+use bevy_ecs::{component::Component, entity::Entity, world::World};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{entity::EntityPtr, BevyObject, EntityResolver, WorldExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct StableId(u64);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Target(#[serde(with = "EntityPtr")] Entity);
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Turret(String);
+
+#[derive(BevyObject)]
+pub struct SerializeTurret {
+    turret: Turret,
+    target: Target,
+}
+
+pub struct ByStableId;
+
+impl EntityResolver for ByStableId {
+    fn save(&self, world: &World, entity: Entity) -> Option<u64> {
+        world.get::<StableId>(entity).map(|x| x.0)
+    }
+
+    fn load(&self, world: &mut World, id: u64) -> Option<Entity> {
+        world
+            .query::<(Entity, &StableId)>()
+            .iter(world)
+            .find(|(_, x)| x.0 == id)
+            .map(|(entity, _)| entity)
+    }
+}
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    let player = world.spawn(StableId(7)).id();
+    world.spawn((Turret("North".to_owned()), Target(player)));
+
+    world.register_entity_resolver(ByStableId);
+    let value = serde_json::to_value(world.serialize_lens::<SerializeTurret>()).unwrap();
+    assert_eq!(value, json!([{"turret": "North", "target": 7}]));
+
+    world.despawn_bound_objects::<SerializeTurret>();
+    world.despawn(player);
+    let player = world.spawn(StableId(7)).id();
+    world.load::<SerializeTurret, _>(&value).unwrap();
+    let (_, target) = world.query::<(&Turret, &Target)>().single(&world);
+    assert_eq!(target.0, player);
+
+    world.despawn(player);
+    assert!(world
+        .load::<SerializeTurret, _>(&json!([{"turret": "South", "target": 7}]))
+        .is_err());
+}
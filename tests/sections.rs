@@ -0,0 +1,63 @@
+use bevy_ecs::{component::Component, system::Resource, world::World};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{
+    batch, BevyObject, IncrementalLoad, LoadBudget, SectionPolicy, SerializeResource,
+    WorldExtension,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Serialize, Deserialize, Component, TypePath)]
+#[serde(transparent)]
+pub struct Unit(String);
+
+#[derive(Serialize, Deserialize, Resource, TypePath)]
+#[serde(transparent)]
+pub struct Gold(u32);
+
+#[derive(BevyObject)]
+#[bevy_object(query)]
+pub struct SerializeUnit {
+    unit: Unit,
+}
+
+type SaveFile = batch!(SerializeUnit, SerializeResource<Gold>);
+
+#[test]
+pub fn test() {
+    let missing = json!({
+        "SerializeUnit": [{"unit": "Alice"}],
+    });
+    let unknown = json!({
+        "SerializeUnit": [{"unit": "Bob"}],
+        "Weather": {"rain": true},
+        "Gold": 3,
+    });
+
+    let mut world = World::new();
+    world.load::<SaveFile, _>(&missing).unwrap();
+    assert!(world.load::<SaveFile, _>(&unknown).is_err());
+    assert_eq!(world.entities().len(), 1);
+
+    world.insert_resource(SectionPolicy::strict());
+    let err = world.load::<SaveFile, _>(&missing).unwrap_err();
+    assert_eq!(err.to_string(), "Missing section Gold.");
+    assert!(world.load::<SaveFile, _>(&unknown).is_err());
+    assert_eq!(world.entities().len(), 1);
+
+    world.insert_resource(SectionPolicy::forward_compatible());
+    world.load::<SaveFile, _>(&missing).unwrap();
+    let report = world.load::<SaveFile, _>(&unknown).unwrap();
+    assert_eq!(report.unknown_sections, vec!["Weather".to_owned()]);
+    assert_eq!(report.get("SerializeUnit").len(), 1);
+    assert_eq!(world.resource::<Gold>().0, 3);
+    assert_eq!(world.entities().len(), 3);
+
+    let mut loader = IncrementalLoad::<SaveFile>::new(&unknown).unwrap();
+    while !loader.step(&mut world, LoadBudget::Entities(1)).unwrap() {}
+    assert_eq!(world.entities().len(), 4);
+
+    world.insert_resource(SectionPolicy::default());
+    let mut loader = IncrementalLoad::<SaveFile>::new(&unknown).unwrap();
+    assert!(loader.step(&mut world, LoadBudget::Entities(100)).is_err());
+}
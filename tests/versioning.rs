@@ -0,0 +1,177 @@
+use bevy_ecs::{component::Component, system::Resource, world::World};
+use bevy_reflect::TypePath;
+use bevy_serde_lens::{batch, value::Value, BevyObject, SerializeResource, WorldExtension};
+use postcard::ser_flavors::Flavor;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize, Component, TypePath, PartialEq)]
+pub struct Unit {
+    name: String,
+    health: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Resource, TypePath, PartialEq)]
+pub struct Gold(u32);
+
+#[derive(BevyObject)]
+pub struct SerializeUnit {
+    unit: Unit,
+}
+
+type SaveFile = batch!(SerializeUnit, SerializeResource<Gold>);
+
+#[test]
+pub fn test() {
+    let mut world = World::new();
+    world.spawn(Unit {
+        name: "Bob".to_owned(),
+        health: 10,
+    });
+    world.insert_resource(Gold(5));
+
+    let value = world
+        .save_versioned::<SaveFile, _>(2, serde_json::value::Serializer)
+        .unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "version": 2,
+            "data": {
+                "SerializeUnit": [{"unit": {"name": "Bob", "health": 10}}],
+                "Gold": 5,
+            }
+        })
+    );
+
+    let mut serializer = postcard::Serializer {
+        output: postcard::ser_flavors::AllocVec::new(),
+    };
+    world
+        .save_versioned::<SaveFile, _>(2, &mut serializer)
+        .unwrap();
+    let bytes = serializer.output.finalize().unwrap();
+
+    world.despawn_bound_objects::<SaveFile>();
+
+    // version 0 to 1: `hp` was renamed to `health`.
+    world.register_migration("SerializeUnit", 0, |value| {
+        let Value::Seq(items) = value else {
+            return Err("expected a sequence.".to_owned());
+        };
+        for item in items {
+            let unit = item.get_mut("unit").ok_or("missing unit.")?;
+            let hp = unit.remove("hp").ok_or("missing hp.")?;
+            unit.insert("health", hp);
+        }
+        Ok(())
+    });
+    // version 1 to 2: gold is multiplied by 10.
+    world.register_migration("Gold", 1, |value| {
+        let Value::U64(gold) = value else {
+            return Err("expected an integer.".to_owned());
+        };
+        *gold *= 10;
+        Ok(())
+    });
+
+    // No migration needed, works on non-self-describing formats.
+    let mut deserializer = postcard::Deserializer::from_bytes(&bytes);
+    world
+        .load_versioned::<SaveFile, _>(2, &mut deserializer)
+        .unwrap();
+    assert_eq!(world.resource::<Gold>(), &Gold(5));
+    world.despawn_bound_objects::<SaveFile>();
+
+    world
+        .load_versioned::<SaveFile, _>(
+            2,
+            json!({
+                "version": 0,
+                "data": {
+                    "SerializeUnit": [{"unit": {"name": "Alice", "hp": 20}}],
+                    "Gold": 3,
+                }
+            }),
+        )
+        .unwrap();
+    let mut query = world.query::<&Unit>();
+    assert_eq!(
+        query.single(&world),
+        &Unit {
+            name: "Alice".to_owned(),
+            health: 20
+        }
+    );
+    assert_eq!(world.resource::<Gold>(), &Gold(30));
+    world.despawn_bound_objects::<SaveFile>();
+
+    world
+        .load_versioned::<SaveFile, _>(
+            2,
+            json!({
+                "version": 1,
+                "data": {
+                    "SerializeUnit": [{"unit": {"name": "Alice", "health": 20}}],
+                    "Gold": 3,
+                }
+            }),
+        )
+        .unwrap();
+    assert_eq!(world.resource::<Gold>(), &Gold(30));
+    world.despawn_bound_objects::<SaveFile>();
+
+    // Single sections are migrated directly.
+    world
+        .load_versioned::<SerializeUnit, _>(
+            2,
+            json!({
+                "version": 0,
+                "data": [{"unit": {"name": "Alice", "hp": 20}}],
+            }),
+        )
+        .unwrap();
+    assert_eq!(query.iter(&world).count(), 1);
+    world.despawn_bound_objects::<SaveFile>();
+
+    let err = world
+        .load_versioned::<SaveFile, _>(
+            2,
+            json!({
+                "version": 0,
+                "data": {
+                    "SerializeUnit": [{"unit": {"name": "Alice"}}],
+                    "Gold": 3,
+                }
+            }),
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("missing hp."));
+    assert_eq!(world.entities().len(), 0);
+    assert!(world.get_resource::<Gold>().is_none());
+}
+
+#[test]
+pub fn test_envelope() {
+    let mut world = World::new();
+
+    // `data` may appear before `version`.
+    let json = r#"{"data": {"SerializeUnit": [], "Gold": 3}, "version": 1}"#;
+    world
+        .load_versioned::<SaveFile, _>(2, &mut serde_json::Deserializer::from_str(json))
+        .unwrap();
+    assert_eq!(world.resource::<Gold>(), &Gold(3));
+    world.despawn_bound_objects::<SaveFile>();
+
+    // Saves from a newer version are rejected.
+    let json = r#"{"version": 3, "data": {"SerializeUnit": [], "Gold": 3}}"#;
+    let err = world
+        .load_versioned::<SaveFile, _>(2, &mut serde_json::Deserializer::from_str(json))
+        .unwrap_err();
+    assert!(err.to_string().contains("newer"));
+    assert!(world.get_resource::<Gold>().is_none());
+
+    let mut value = Value::U32(1);
+    assert!(value.insert("key", Value::Unit).is_none());
+    assert_eq!(value, Value::U32(1));
+}